@@ -0,0 +1,106 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{ Path },
+    str::FromStr,
+};
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use crate::manifest::Manifest;
+
+/// Logical names of pubkeys, e.g. `voter1` or `governance wBAL`, used to annotate output and
+/// accepted instead of base58 addresses in CLI arguments. The first name added for a pubkey wins
+#[derive(Debug, Clone, Default)]
+pub struct AddressBook {
+    names: BTreeMap<Pubkey, String>,
+    addresses: BTreeMap<String, Pubkey>,
+}
+
+impl AddressBook {
+    /// TOML file of `name = "<pubkey>"` lines
+    pub fn load(path: &Path) -> Result<Self,Box<dyn Error>> {
+        let content: String = fs::read_to_string(path)?;
+        let entries: BTreeMap<String, String> = toml::from_str(&content)?;
+
+        let mut address_book: AddressBook = AddressBook::default();
+        for (name, address) in entries.iter() {
+            let pubkey: Pubkey = Pubkey::from_str(address).map_err(|err| format!("{} {}: {}", name, address, err) )?;
+            address_book.insert(name, &pubkey);
+        }
+        Ok(address_book)
+    }
+
+    /// Empty address book if there is no file
+    pub fn load_or_default(path: &Path) -> Result<Self,Box<dyn Error>> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(AddressBook::default())
+        }
+    }
+
+    /// Labels of the manifest, then the names of its accounts
+    pub fn add_manifest(&mut self, manifest: &Manifest) {
+        for (address, label) in manifest.labels.iter() {
+            if let Ok(pubkey) = Pubkey::from_str(address) {
+                self.insert(label, &pubkey);
+            }
+        }
+        for (name, entry) in manifest.accounts.iter() {
+            if let Ok(pubkey) = Pubkey::from_str(&entry.address) {
+                self.insert(name, &pubkey);
+            }
+        }
+    }
+
+    /// Keeps the existing name of `pubkey` and the existing pubkey of `name`
+    pub fn insert(&mut self, name: &str, pubkey: &Pubkey) {
+        self.names.entry(*pubkey).or_insert_with(|| name.to_string() );
+        self.addresses.entry(name.to_string()).or_insert(*pubkey);
+    }
+
+    pub fn get_name(&self, pubkey: &Pubkey) -> Option<&str> {
+        self.names.get(pubkey).map(|name| name.as_str() )
+    }
+
+    pub fn get_address(&self, name: &str) -> Option<Pubkey> {
+        self.addresses.get(name).copied()
+    }
+
+    /// `<pubkey> (<name>)`, the bare pubkey if it has no name
+    pub fn annotate(&self, pubkey: &Pubkey) -> String {
+        match self.get_name(pubkey) {
+            Some(name) => format!("{} ({})", pubkey, name),
+            None => pubkey.to_string(),
+        }
+    }
+
+    /// Annotates every named base58 pubkey found in `text`, e.g. a log line or a drift report
+    pub fn annotate_text(&self, text: &str) -> String {
+        let mut result: String = String::with_capacity(text.len());
+        let mut word: String = String::new();
+        for c in text.chars().chain(std::iter::once(' ')) {
+            if c.is_ascii_alphanumeric() {
+                word.push(c);
+                continue;
+            }
+            match Pubkey::from_str(&word).ok().and_then(|pubkey| self.get_name(&pubkey) ) {
+                Some(name) => result.push_str(&format!("{} ({})", word, name)),
+                None => result.push_str(&word),
+            }
+            word.clear();
+            result.push(c);
+        }
+        result.pop();
+        result
+    }
+
+    /// CLI argument: a base58 pubkey or a name of the address book
+    pub fn resolve(&self, name_or_address: &str) -> Result<Pubkey,String> {
+        self.get_address(name_or_address)
+            .or_else(|| Pubkey::from_str(name_or_address).ok() )
+            .ok_or_else(|| format!("{} is neither a pubkey nor a name of the address book", name_or_address) )
+    }
+}
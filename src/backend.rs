@@ -0,0 +1,380 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::thread;
+use std::time::{ Duration, Instant };
+
+use solana_sdk::{
+    account::{ Account, AccountSharedData },
+    clock::{ Clock, Slot, DEFAULT_MS_PER_SLOT },
+    commitment_config::CommitmentConfig,
+    hash::{ Hash },
+    pubkey::{ Pubkey },
+    rent::{ Rent },
+    signature::{ Signature },
+    signer::{
+        keypair::{ Keypair },
+    },
+    sysvar,
+    transaction::{ Transaction, TransactionError },
+};
+
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{ RpcSendTransactionConfig, RpcProgramAccountsConfig, RpcAccountInfoConfig };
+use solana_client::rpc_filter::{ RpcFilterType, Memcmp, MemcmpEncodedBytes };
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_client::client_error::Result as ClientResult;
+use solana_client::client_error::ClientErrorKind;
+
+use solana_program_test::{ ProgramTest, ProgramTestContext, processor };
+
+use solana_account_decoder::UiAccountEncoding;
+
+use tokio::runtime::Runtime;
+
+use crate::send::{ SendStrategy, is_transport_error };
+
+/// `bytes` at `offset` of the account data (memcmp filter)
+#[derive(Debug,Clone)]
+pub struct AccountFilter {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl AccountFilter {
+    pub fn new(offset: usize, bytes: &[u8]) -> Self {
+        AccountFilter {
+            offset,
+            bytes: bytes.to_vec(),
+        }
+    }
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.get(self.offset..self.offset + self.bytes.len()) == Some(&self.bytes[..])
+    }
+}
+
+/// Ledger access used by `SplGovernanceInteractor`
+pub trait Backend {
+    fn get_account(&self, address: &Pubkey, commitment: CommitmentConfig) -> ClientResult<Option<Account>>;
+    /// Accounts owned by `program_id` whose data matches all `filters`.
+    /// The program-test backend only finds accounts referenced by its own transactions or written by `set_account`,
+    /// not those added to the `ProgramTest` before it was started
+    fn get_program_accounts(&self, program_id: &Pubkey, filters: &[AccountFilter], commitment: CommitmentConfig) -> ClientResult<Vec<(Pubkey, Account)>>;
+    fn get_latest_blockhash(&self, commitment: CommitmentConfig) -> ClientResult<Hash>;
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64>;
+    fn get_clock(&self) -> ClientResult<Clock>;
+    /// Single attempt: retries are up to the caller. A transaction with a durable nonce
+    /// does not expire with its blockhash
+    fn send_and_confirm_transaction(&self, transaction: &Transaction, send_strategy: &SendStrategy, durable_nonce: bool) -> ClientResult<Signature>;
+    fn simulate_transaction(&self, transaction: &Transaction) -> ClientResult<RpcSimulateTransactionResult>;
+    /// Moves the cluster time at least `seconds` forward (and the slot accordingly), returns the new clock
+    fn advance_clock(&self, seconds: u64) -> ClientResult<Clock>;
+    /// Moves the cluster to `slot` (not earlier than the current one), returns the new clock
+    fn warp_to_slot(&self, slot: Slot) -> ClientResult<Clock>;
+    /// Overrides the `Clock` sysvar seen by programs
+    fn set_clock(&self, clock: &Clock) -> ClientResult<()>;
+    /// Creates or overwrites an account, bypassing the programs
+    fn set_account(&self, address: &Pubkey, account: &Account) -> ClientResult<()>;
+}
+
+pub struct RpcBackend {
+    solana_client: RpcClient,
+}
+
+impl RpcBackend {
+    pub fn new(url: &str) -> Self {
+        RpcBackend {
+            solana_client: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+        }
+    }
+    pub fn get_client(&self) -> &RpcClient {
+        &self.solana_client
+    }
+}
+
+impl Backend for RpcBackend {
+    fn get_account(&self, address: &Pubkey, commitment: CommitmentConfig) -> ClientResult<Option<Account>> {
+        self.solana_client.get_account_with_commitment(address, commitment)
+            .map(|response| response.value )
+    }
+
+    fn get_program_accounts(&self, program_id: &Pubkey, filters: &[AccountFilter], commitment: CommitmentConfig) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let config: RpcProgramAccountsConfig =
+            RpcProgramAccountsConfig {
+                filters: Some(
+                    filters.iter()
+                        .map(|filter|
+                            RpcFilterType::Memcmp(
+                                Memcmp {
+                                    offset: filter.offset,
+                                    bytes: MemcmpEncodedBytes::Base64(base64::encode(&filter.bytes)),
+                                    encoding: None,
+                                }
+                            )
+                        )
+                        .collect()
+                ),
+                account_config:
+                    RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        commitment: Some(commitment),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                ..RpcProgramAccountsConfig::default()
+            };
+        self.solana_client.get_program_accounts_with_config(program_id, config)
+    }
+
+    fn get_latest_blockhash(&self, commitment: CommitmentConfig) -> ClientResult<Hash> {
+        self.solana_client.get_latest_blockhash_with_commitment(commitment)
+            .map(|(blockhash,_)| blockhash )
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.solana_client.get_minimum_balance_for_rent_exemption(data_len)
+    }
+
+    fn get_clock(&self) -> ClientResult<Clock> {
+        let account: Account = self.solana_client.get_account(&sysvar::clock::id())?;
+        bincode::deserialize(&account.data)
+            .map_err(|e| ClientErrorKind::Custom(e.to_string()).into() )
+    }
+
+    /// Sends and polls the signature status until the transaction reaches the strategy commitment or its blockhash expires
+    /// (the confirmation timeout of the strategy for a durable nonce transaction).
+    /// Transport errors never end the attempt: the status is polled again and the same signed transaction is resent
+    /// until a send gets through, so it cannot land twice
+    fn send_and_confirm_transaction(&self, transaction: &Transaction, send_strategy: &SendStrategy, durable_nonce: bool) -> ClientResult<Signature> {
+        let send_config: RpcSendTransactionConfig =
+            RpcSendTransactionConfig {
+                skip_preflight: send_strategy.skip_preflight,
+                preflight_commitment: Some(send_strategy.commitment.commitment),
+                ..RpcSendTransactionConfig::default()
+            };
+        let signature: Signature = transaction.signatures[0];
+        let blockhash: &Hash = &transaction.message.recent_blockhash;
+        let started: Instant = Instant::now();
+
+        let mut sent: bool = false;
+        loop {
+            if !sent {
+                match self.solana_client.send_transaction_with_config(transaction, send_config) {
+                    Ok(_) => sent = true,
+                    // An earlier send did get through after all
+                    Err(error) if error.get_transaction_error() == Some(TransactionError::AlreadyProcessed) => sent = true,
+                    Err(error) if is_transport_error(&error) => {},
+                    Err(error) => return Err(error),
+                }
+            }
+
+            match self.solana_client.get_signature_status_with_commitment(&signature, send_strategy.commitment) {
+                Ok(Some(status)) => {
+                    return status
+                        .map(|_| signature )
+                        .map_err(|err| ClientErrorKind::TransactionError(err).into() );
+                },
+                Ok(None) => {},
+                Err(error) if is_transport_error(&error) => {},
+                Err(error) => return Err(error),
+            }
+
+            if durable_nonce {
+                if started.elapsed() > send_strategy.confirmation_timeout {
+                    return Err(ClientErrorKind::Custom(format!("Transaction {} not confirmed within {:?}, it stays valid until the nonce is advanced", signature, send_strategy.confirmation_timeout)).into());
+                }
+            } else {
+                match self.solana_client.is_blockhash_valid(blockhash, send_strategy.commitment) {
+                    Ok(false) => return Err(ClientErrorKind::TransactionError(TransactionError::BlockhashNotFound).into()),
+                    Ok(true) => {},
+                    Err(error) if is_transport_error(&error) => {},
+                    Err(error) => return Err(error),
+                }
+            }
+            thread::sleep(send_strategy.poll_interval);
+        }
+    }
+
+    fn simulate_transaction(&self, transaction: &Transaction) -> ClientResult<RpcSimulateTransactionResult> {
+        self.solana_client.simulate_transaction(transaction)
+            .map(|response| response.value )
+    }
+
+    /// A real cluster cannot be warped: waits until its clock gets there
+    fn advance_clock(&self, seconds: u64) -> ClientResult<Clock> {
+        let target_timestamp: i64 = self.get_clock()?.unix_timestamp + seconds as i64;
+        loop {
+            let clock: Clock = self.get_clock()?;
+            if clock.unix_timestamp >= target_timestamp {
+                return Ok(clock);
+            }
+            thread::sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT));
+        }
+    }
+
+    fn warp_to_slot(&self, slot: Slot) -> ClientResult<Clock> {
+        loop {
+            let clock: Clock = self.get_clock()?;
+            if clock.slot >= slot {
+                return Ok(clock);
+            }
+            thread::sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT));
+        }
+    }
+
+    fn set_clock(&self, _clock: &Clock) -> ClientResult<()> {
+        Err(ClientErrorKind::Custom("Clock of a cluster cannot be set".to_string()).into())
+    }
+
+    fn set_account(&self, address: &Pubkey, _account: &Account) -> ClientResult<()> {
+        Err(ClientErrorKind::Custom(format!("Account {} of a cluster cannot be set", address)).into())
+    }
+}
+
+/// In-process bank (`solana-program-test`) with the governance program and the voter weight addins loaded
+pub struct ProgramTestBackend {
+    runtime: Runtime,
+    context: RefCell<ProgramTestContext>,
+    /// Accounts referenced by processed transactions or set directly: the banks client cannot enumerate program accounts
+    known_addresses: RefCell<BTreeSet<Pubkey>>,
+}
+
+impl ProgramTestBackend {
+    /// spl-governance with the fixed-weights and mock voter weight addins at the given addresses
+    pub fn program_test(governance_program_id: &Pubkey, fixed_weights_addin_id: &Pubkey, mock_addin_id: &Pubkey) -> ProgramTest {
+        let mut program_test: ProgramTest =
+            ProgramTest::new(
+                "spl_governance",
+                *governance_program_id,
+                processor!(spl_governance::processor::process_instruction),
+            );
+        program_test.add_program(
+            "spl_governance_addin_fixed_weights",
+            *fixed_weights_addin_id,
+            processor!(spl_governance_addin_fixed_weights::processor::process_instruction),
+        );
+        program_test.add_program(
+            "spl_governance_addin_mock",
+            *mock_addin_id,
+            processor!(spl_governance_addin_mock::processor::process_instruction),
+        );
+        program_test
+    }
+
+    pub fn new(program_test: ProgramTest) -> Self {
+        let runtime: Runtime =
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+        let context: ProgramTestContext = runtime.block_on(program_test.start_with_context());
+
+        ProgramTestBackend {
+            runtime,
+            context: RefCell::new(context),
+            known_addresses: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    /// Funded genesis account, usable as the interactor payer
+    pub fn get_payer(&self) -> Keypair {
+        Keypair::from_bytes(&self.context.borrow().payer.to_bytes()).unwrap()
+    }
+}
+
+impl Backend for ProgramTestBackend {
+    fn get_account(&self, address: &Pubkey, _commitment: CommitmentConfig) -> ClientResult<Option<Account>> {
+        let mut context = self.context.borrow_mut();
+        Ok(self.runtime.block_on(context.banks_client.get_account(*address))?)
+    }
+
+    /// Searches the accounts of all processed transactions and those set by `set_account`
+    fn get_program_accounts(&self, program_id: &Pubkey, filters: &[AccountFilter], commitment: CommitmentConfig) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let known_addresses: Vec<Pubkey> = self.known_addresses.borrow().iter().cloned().collect();
+        let mut accounts: Vec<(Pubkey, Account)> = Vec::new();
+        for address in known_addresses.into_iter() {
+            if let Some(account) = self.get_account(&address, commitment)? {
+                if account.owner == *program_id && filters.iter().all(|filter| filter.matches(&account.data) ) {
+                    accounts.push((address, account));
+                }
+            }
+        }
+        Ok(accounts)
+    }
+
+    fn get_latest_blockhash(&self, _commitment: CommitmentConfig) -> ClientResult<Hash> {
+        let mut context = self.context.borrow_mut();
+        Ok(self.runtime.block_on(context.banks_client.get_latest_blockhash())?)
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        let mut context = self.context.borrow_mut();
+        let rent: Rent = self.runtime.block_on(context.banks_client.get_rent())?;
+        Ok(rent.minimum_balance(data_len))
+    }
+
+    fn get_clock(&self) -> ClientResult<Clock> {
+        let mut context = self.context.borrow_mut();
+        Ok(self.runtime.block_on(context.banks_client.get_sysvar::<Clock>())?)
+    }
+
+    /// Transactions are processed synchronously by the bank, the strategy does not apply
+    fn send_and_confirm_transaction(&self, transaction: &Transaction, _send_strategy: &SendStrategy, _durable_nonce: bool) -> ClientResult<Signature> {
+        self.known_addresses.borrow_mut().extend(transaction.message.account_keys.iter().cloned());
+        let mut context = self.context.borrow_mut();
+        self.runtime.block_on(context.banks_client.process_transaction(transaction.clone()))
+            .map(|_| transaction.signatures[0] )
+            .map_err(|e| e.into() )
+    }
+
+    /// Processes the transaction on a fork of the bank, nothing is committed
+    fn simulate_transaction(&self, transaction: &Transaction) -> ClientResult<RpcSimulateTransactionResult> {
+        let mut context = self.context.borrow_mut();
+        let simulation = self.runtime.block_on(context.banks_client.simulate_transaction(transaction.clone()))
+            .map_err(|e| ClientErrorKind::Custom(format!("Simulation failed: {}", e)) )?;
+        let (logs, units_consumed): (Option<Vec<String>>, Option<u64>) =
+            match simulation.simulation_details {
+                Some(details) => (Some(details.logs), Some(details.units_consumed)),
+                None => (None, None),
+            };
+
+        Ok(
+            RpcSimulateTransactionResult {
+                err: simulation.result.and_then(|result| result.err() ),
+                logs,
+                accounts: None,
+                units_consumed,
+            }
+        )
+    }
+
+    /// Warps to the slot the time corresponds to, then overrides the clock timestamp
+    fn advance_clock(&self, seconds: u64) -> ClientResult<Clock> {
+        let clock: Clock = self.get_clock()?;
+        let target_timestamp: i64 = clock.unix_timestamp + seconds as i64;
+
+        let mut clock: Clock = self.warp_to_slot(clock.slot + 1 + seconds * 1000 / DEFAULT_MS_PER_SLOT)?;
+        clock.unix_timestamp = clock.unix_timestamp.max(target_timestamp);
+        self.set_clock(&clock)?;
+        Ok(clock)
+    }
+
+    /// New bank at `slot`: also gives a fresh blockhash, so identical transactions can be sent again
+    fn warp_to_slot(&self, slot: Slot) -> ClientResult<Clock> {
+        let current_slot: Slot = self.get_clock()?.slot;
+        if slot > current_slot {
+            self.context.borrow_mut().warp_to_slot(slot)
+                .map_err(|e| ClientErrorKind::Custom(format!("Warp to slot {} failed: {:?}", slot, e)) )?;
+        }
+        self.get_clock()
+    }
+
+    fn set_clock(&self, clock: &Clock) -> ClientResult<()> {
+        self.context.borrow().set_sysvar(clock);
+        Ok(())
+    }
+
+    fn set_account(&self, address: &Pubkey, account: &Account) -> ClientResult<()> {
+        self.known_addresses.borrow_mut().insert(*address);
+        self.context.borrow_mut().set_account(address, &AccountSharedData::from(account.clone()));
+        Ok(())
+    }
+}
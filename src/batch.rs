@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use solana_sdk::{
+    pubkey::{ Pubkey },
+    instruction::{ Instruction },
+    message::{ Message },
+    packet::{ PACKET_DATA_SIZE },
+    signer::{ Signer },
+    signature::{ Signature },
+};
+
+use solana_client::client_error::Result as ClientResult;
+use solana_client::client_error::ClientErrorKind;
+
+use spl_governance::state::vote_record::{ Vote };
+
+use crate::commands::{ SplGovernanceInteractor, Realm, Governance, Proposal, TokenOwner };
+use crate::manifest::AccountKind;
+
+/// Instructions that must land in the same transaction, with the signers they require
+struct Operation {
+    instructions: Vec<Instruction>,
+    signers: Vec<Arc<dyn Signer>>,
+}
+
+/// Collects instructions of several interactor operations and sends them
+/// packed into as few transactions as fit `PACKET_DATA_SIZE`.
+/// Operations are never split across transactions and keep their order.
+pub struct TransactionBatch<'a> {
+    interactor: &'a SplGovernanceInteractor,
+    operations: Vec<Operation>,
+    /// Recorded in the manifest once all transactions are sent
+    accounts: Vec<(AccountKind, Pubkey, Pubkey)>,
+}
+
+impl<'a> TransactionBatch<'a> {
+
+    pub fn new(interactor: &'a SplGovernanceInteractor) -> Self {
+        TransactionBatch {
+            interactor,
+            operations: Vec::new(),
+            accounts: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, instructions: Vec<Instruction>, signers: Vec<Arc<dyn Signer>>) -> &mut Self {
+        self.operations.push(Operation { instructions, signers });
+        self
+    }
+
+    /// Skipped if the record already exists
+    pub fn create_token_owner_record(&mut self, realm: &Realm, token_owner_pubkey: &Pubkey) -> &mut Self {
+        let token_owner_record_pubkey: Pubkey = self.interactor.get_token_owner_record_address(token_owner_pubkey, &realm.get_community_mint(), &realm.get_name());
+        if !self.interactor.account_exists(&token_owner_record_pubkey) {
+            let instruction: Instruction = self.interactor.create_token_owner_record_instruction(realm, token_owner_pubkey);
+            self.add(vec![ instruction ], vec![]);
+        }
+        self.accounts.push((AccountKind::TokenOwnerRecord, *token_owner_pubkey, token_owner_record_pubkey));
+        self
+    }
+
+    /// Skipped if the record already exists
+    pub fn setup_voter_weight_record_fixed(&mut self, realm: &Realm, token_owner_pubkey: &Pubkey) -> &mut Self {
+        let voter_weight_record_pubkey: Pubkey = self.interactor.get_voter_weight_record_address_fixed(realm, token_owner_pubkey);
+        if !self.interactor.account_exists(&voter_weight_record_pubkey) {
+            let instruction: Instruction = self.interactor.setup_voter_weight_record_fixed_instruction(realm, token_owner_pubkey);
+            self.add(vec![ instruction ], vec![]);
+        }
+        self.accounts.push((AccountKind::VoterWeightRecord, *token_owner_pubkey, voter_weight_record_pubkey));
+        self
+    }
+
+    pub fn cast_vote(&mut self, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner, vote: Vote) -> &mut Self {
+        let instruction: Instruction = self.interactor.cast_vote_instruction(realm, governance, proposal, voter, vote);
+        self.add(vec![ instruction ], vec![ voter.authority.clone() ])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Groups operations into transactions: (instructions, deduplicated signers).
+    /// Sizes include the durable nonce instruction and signature `process_transaction` adds.
+    /// Fails if an operation does not fit into a transaction on its own
+    fn pack(&self) -> ClientResult<Vec<(Vec<Instruction>, Vec<Arc<dyn Signer>>)>> {
+        let mut transactions: Vec<(Vec<Instruction>, Vec<Arc<dyn Signer>>)> = Vec::new();
+        let mut instructions: Vec<Instruction> = Vec::new();
+        let mut signers: Vec<Arc<dyn Signer>> = Vec::new();
+
+        for (index, operation) in self.operations.iter().enumerate() {
+            let operation_size: usize = self.get_transaction_size(&operation.instructions);
+            if operation_size > PACKET_DATA_SIZE {
+                return Err(ClientErrorKind::Custom(format!("Operation {} does not fit into a transaction: {} bytes, limit {}", index, operation_size, PACKET_DATA_SIZE)).into());
+            }
+
+            let mut candidate: Vec<Instruction> = instructions.clone();
+            candidate.extend(operation.instructions.iter().cloned());
+
+            if !instructions.is_empty() && self.get_transaction_size(&candidate) > PACKET_DATA_SIZE {
+                transactions.push((instructions, signers));
+                instructions = operation.instructions.clone();
+                signers = Vec::new();
+            } else {
+                instructions = candidate;
+            }
+
+            for signer in operation.signers.iter() {
+                if !signers.iter().any(|s| s.pubkey() == signer.pubkey() ) {
+                    signers.push(signer.clone());
+                }
+            }
+        }
+
+        if !instructions.is_empty() {
+            transactions.push((instructions, signers));
+        }
+        Ok(transactions)
+    }
+
+    /// Serialized size of every transaction `execute` sends
+    pub fn get_transaction_sizes(&self) -> ClientResult<Vec<usize>> {
+        Ok(
+            self.pack()?.iter()
+                .map(|(instructions, _)| self.get_transaction_size(instructions) )
+                .collect()
+        )
+    }
+
+    /// Size of the transaction `process_transaction` builds from `instructions`
+    fn get_transaction_size(&self, instructions: &[Instruction]) -> usize {
+        let mut all_instructions: Vec<Instruction> = self.interactor.get_prefix_instructions();
+        all_instructions.extend_from_slice(instructions);
+        transaction_size(&self.interactor.get_payer_pubkey(), &all_instructions)
+    }
+
+    /// Sends the packed transactions in order, stopping at the first failure
+    pub fn execute(self) -> ClientResult<Vec<Signature>> {
+        let mut signatures: Vec<Signature> = Vec::new();
+        for (instructions, signers) in self.pack()? {
+            let signers: Vec<&dyn Signer> = signers.iter().map(|s| s.as_ref() ).collect();
+            signatures.push(self.interactor.send_transaction(&instructions, &signers)?);
+        }
+        for (kind, token_owner_pubkey, address) in self.accounts.iter() {
+            self.interactor.record_account(*kind, &self.interactor.get_label(token_owner_pubkey), address)?;
+        }
+        Ok(signatures)
+    }
+}
+
+/// Serialized size of the signed transaction: signatures (with their short_vec length) and the message
+fn transaction_size(payer: &Pubkey, instructions: &[Instruction]) -> usize {
+    let message: Message = Message::new(instructions, Some(payer));
+    let signatures_count: usize = message.header.num_required_signatures as usize;
+    let signatures_count_len: usize = if signatures_count < 0x80 { 1 } else { 2 };
+
+    signatures_count_len + signatures_count * 64 + message.serialize().len()
+}
@@ -1,18 +1,32 @@
+use std::cell::{ Cell, RefCell };
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::sync::Arc;
+use std::thread;
+
 use solana_sdk::{
-    commitment_config::CommitmentConfig,
+    account::{ Account },
+    clock::{ Clock, Slot, UnixTimestamp },
+    hash::{ Hash },
     pubkey::{ Pubkey },
-    instruction::{ Instruction },
+    instruction::{ Instruction, AccountMeta },
     transaction::{ Transaction },
     signer::{
         Signer,
         keypair::{ Keypair },
     },
     signature::Signature,
+    system_instruction,
+    nonce::State as NonceState,
+    program_pack::{ Pack },
 };
 
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonce_utils;
 use solana_client::client_error::Result as ClientResult;
 use solana_client::client_error::ClientError;
+use solana_client::client_error::ClientErrorKind;
 
 use borsh::{BorshDeserialize};
 
@@ -22,6 +36,7 @@ use spl_governance::{
             // VoteThresholdPercentage,
             // VoteWeightSource,
             // VoteTipping,
+            GovernanceAccountType,
             MintMaxVoteWeightSource,
         },
         governance::{
@@ -33,11 +48,20 @@ use spl_governance::{
             RealmV2,
             get_realm_address,
         },
+        realm_config::{
+            RealmConfigAccount,
+            get_realm_config_address,
+        },
         proposal::{
             VoteType,
             ProposalV2,
             get_proposal_address,
         },
+        proposal_transaction::{
+            InstructionData,
+            ProposalTransactionV2,
+            get_proposal_transaction_address,
+        },
         token_owner_record::{
             TokenOwnerRecordV2,
             get_token_owner_record_address,
@@ -48,17 +72,24 @@ use spl_governance::{
         vote_record::{
             Vote,
             VoteChoice,
+            VoteRecordV2,
         },
     },
     instruction::{
         create_realm,
+        set_realm_config,
         create_token_owner_record,
         create_governance,
-        // set_governance_config,
+        set_governance_config,
         create_proposal,
         sign_off_proposal,
         add_signatory,
         cast_vote,
+        relinquish_vote,
+        set_governance_delegate,
+        insert_transaction,
+        execute_transaction,
+        finalize_vote,
     }
 };
 
@@ -84,25 +115,446 @@ use spl_governance_addin_fixed_weights::{
     }
 };
 
+use crate::backend::{ AccountFilter, Backend, RpcBackend };
+use crate::batch::TransactionBatch;
+use crate::simulation::SimulationReport;
+use crate::offline::{ DurableNonce, OfflineTransaction };
+use crate::manifest::{ AccountKind, Manifest, get_account_name };
+use crate::versioned::{ Versioned, VersionedAccount, decode };
+use crate::drift::{ Drift, DriftCheck, DriftPolicy };
+use crate::send::{ SendStrategy, is_retryable };
+
 const MIN_COMMUNITY_WEIGHT_TO_CREATE_GOVERNANCE: u64 = 1;
+/// Offset of the realm in token owner records and governances, of the governance in proposals
+/// and of the proposal in vote records (after the account type)
+const PARENT_ACCOUNT_OFFSET: usize = 1;
+const COMMUNITY_MINT_MAX_VOTE_WEIGHT_SOURCE: MintMaxVoteWeightSource = MintMaxVoteWeightSource::SupplyFraction(10_000_000_000);
 
 pub struct SplGovernanceInteractor {
-    solana_client: RpcClient,
+    backend: Box<dyn Backend>,
     spl_governance_program_address: Pubkey,
     spl_governance_voter_weight_addin_address: Pubkey,
+    /// Fee payer of every transaction and funder of every created account
+    payer: Arc<dyn Signer>,
+    execution_mode: ExecutionMode,
+    /// Transactions simulated or exported instead of sent
+    outcomes: RefCell<Vec<Outcome>>,
+    exported_nonce_blockhash: Cell<Option<Hash>>,
+    durable_nonce: Option<DurableNonce>,
+    send_strategy: RefCell<SendStrategy>,
+    manifest: RefCell<Manifest>,
+    /// Where the manifest is saved after every recorded account, not saved without one
+    manifest_path: Option<PathBuf>,
+    drift_policy: Cell<DriftPolicy>,
+    drifts: RefCell<Vec<Drift>>,
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum ExecutionMode {
+    Send,
+    /// Transactions are only simulated (see `process_transaction`), the reports are collected by `take_outcomes`
+    DryRun,
+    /// Transactions are written to the directory as `OfflineTransaction` files for offline signing
+    Export(PathBuf),
+}
+
+/// What `process_transaction` did with a transaction, depending on the `ExecutionMode`
+#[derive(Debug,Clone)]
+pub enum Outcome {
+    Sent(Signature),
+    Simulated(SimulationReport),
+    /// Partially signed `OfflineTransaction` file
+    Exported(PathBuf),
+}
+
+impl Outcome {
+    /// `None` unless the transaction was sent
+    pub fn get_signature(&self) -> Option<Signature> {
+        match self {
+            Outcome::Sent(signature) => Some(*signature),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Sent(signature) => writeln!(f, "Sent {}", signature),
+            Outcome::Simulated(report) => write!(f, "{}", report),
+            Outcome::Exported(path) => writeln!(f, "Exported {}", path.display()),
+        }
+    }
 }
 
 impl SplGovernanceInteractor {
 
-    pub fn new(url: &str, program_address: Pubkey, addin_address: Pubkey) -> Self {
+    pub fn new(url: &str, program_address: Pubkey, addin_address: Pubkey, payer: Arc<dyn Signer>) -> Self {
+        Self::new_with_backend(Box::new(RpcBackend::new(url)), program_address, addin_address, payer)
+    }
+    /// Interactor on any ledger access, e.g. `ProgramTestBackend` for in-process runs
+    pub fn new_with_backend(backend: Box<dyn Backend>, program_address: Pubkey, addin_address: Pubkey, payer: Arc<dyn Signer>) -> Self {
         SplGovernanceInteractor {
-            solana_client: RpcClient::new_with_commitment(url.to_string(),CommitmentConfig::confirmed()),
+            backend,
             spl_governance_program_address: program_address,
             spl_governance_voter_weight_addin_address: addin_address,
+            payer,
+            execution_mode: ExecutionMode::Send,
+            outcomes: RefCell::new(Vec::new()),
+            exported_nonce_blockhash: Cell::new(None),
+            durable_nonce: None,
+            send_strategy: RefCell::new(SendStrategy::default()),
+            manifest: RefCell::new(Manifest::default()),
+            manifest_path: None,
+            drift_policy: Cell::new(DriftPolicy::Warn),
+            drifts: RefCell::new(Vec::new()),
         }
     }
+    /// Default strategy of all sending operations; its commitment is also used for reads
+    pub fn with_send_strategy(mut self, send_strategy: SendStrategy) -> Self {
+        self.send_strategy = RefCell::new(send_strategy);
+        self
+    }
+    pub fn with_execution_mode(mut self, execution_mode: ExecutionMode) -> Self {
+        self.execution_mode = execution_mode;
+        self
+    }
+    pub fn get_execution_mode(&self) -> &ExecutionMode {
+        &self.execution_mode
+    }
+    pub fn get_backend(&self) -> &dyn Backend {
+        self.backend.as_ref()
+    }
+    pub fn with_durable_nonce(mut self, durable_nonce: DurableNonce) -> Self {
+        self.durable_nonce = Some(durable_nonce);
+        self
+    }
+    /// Continues the manifest at `manifest_path` (if it exists) and saves it there
+    pub fn with_manifest(mut self, manifest_path: PathBuf) -> Result<Self,Box<dyn Error>> {
+        self.manifest = RefCell::new(Manifest::load_or_default(&manifest_path)?);
+        self.manifest_path = Some(manifest_path);
+        Ok(self)
+    }
+    pub fn get_manifest(&self) -> Manifest {
+        self.manifest.borrow().clone()
+    }
+    /// Address recorded in the manifest, e.g. for `governance wBAL`
+    pub fn get_manifest_address(&self, name: &str) -> ClientResult<Pubkey> {
+        self.manifest.borrow().get_address(name)
+            .ok_or_else(|| ClientErrorKind::Custom(format!("{} is not in the manifest", name)).into() )
+    }
+    /// Address of an account recorded by this or an earlier run, e.g. a voter weight record of `voter1`
+    pub fn get_recorded_address(&self, kind: AccountKind, suffix: &str) -> ClientResult<Pubkey> {
+        self.get_manifest_address(&get_account_name(kind, suffix))
+    }
+    /// Name `pubkey` (a voter, governed account...) `label` in the manifest names
+    pub fn set_label(&self, pubkey: &Pubkey, label: &str) {
+        self.manifest.borrow_mut().set_label(pubkey, label);
+    }
+    pub(crate) fn get_label(&self, pubkey: &Pubkey) -> String {
+        self.manifest.borrow().get_label(pubkey)
+    }
+    /// `<governed account label> <index>` if the proposal is in the manifest
+    fn get_proposal_label(&self, proposal_pubkey: &Pubkey) -> String {
+        self.manifest.borrow().get_name(proposal_pubkey)
+            .and_then(|name| name.strip_prefix(&format!("{} ", AccountKind::Proposal)).map(str::to_string) )
+            .unwrap_or_else(|| proposal_pubkey.to_string() )
+    }
+    /// Only sent transactions leave accounts behind, dry runs and exports are not recorded
+    pub(crate) fn record_account(&self, kind: AccountKind, suffix: &str, address: &Pubkey) -> ClientResult<()> {
+        if self.execution_mode != ExecutionMode::Send {
+            return Ok(());
+        }
+        self.manifest.borrow_mut().insert(kind, suffix, address);
+        if let Some(manifest_path) = &self.manifest_path {
+            self.manifest.borrow().save(manifest_path)
+                .map_err(|err| ClientErrorKind::Custom(format!("Manifest {} not saved: {}", manifest_path.display(), err)) )?;
+        }
+        Ok(())
+    }
+    /// How existing realms, token owner records and governances that differ from the requested parameters are handled
+    pub fn with_drift_policy(self, drift_policy: DriftPolicy) -> Self {
+        self.drift_policy.set(drift_policy);
+        self
+    }
+    pub fn set_drift_policy(&self, drift_policy: DriftPolicy) {
+        self.drift_policy.set(drift_policy);
+    }
+    /// Drifts found so far (unless ignored)
+    pub fn take_drifts(&self) -> Vec<Drift> {
+        self.drifts.borrow_mut().drain(..).collect()
+    }
+    /// Applies the drift policy to the drifts of an existing account
+    fn check_drifts(&self, drifts: Vec<Drift>) -> ClientResult<()> {
+        if drifts.is_empty() || self.drift_policy.get() == DriftPolicy::Ignore {
+            return Ok(());
+        }
+        let description: String = drifts.iter().map(|drift| drift.to_string() ).collect::<Vec<String>>().join("; ");
+        self.drifts.borrow_mut().extend(drifts);
+
+        match self.drift_policy.get() {
+            DriftPolicy::Error => Err(ClientErrorKind::Custom(format!("Drift: {}", description)).into()),
+            _ => Ok(()),
+        }
+    }
+    /// Transactions simulated or exported so far
+    pub fn take_outcomes(&self) -> Vec<Outcome> {
+        self.outcomes.borrow_mut().drain(..).collect()
+    }
+    pub fn get_payer_pubkey(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+    pub fn batch(&self) -> TransactionBatch {
+        TransactionBatch::new(self)
+    }
+    /// Like `process_transaction`, for operations that need the transaction to land (e.g. to read the created account):
+    /// a simulated or exported transaction stops them with an error. Operations that only change existing accounts
+    /// use `process_transaction` and return the `Outcome`
+    pub fn send_transaction(&self, instructions: &[Instruction], signers: &[&dyn Signer]) -> ClientResult<Signature> {
+        match self.process_transaction(instructions, signers)? {
+            Outcome::Sent(signature) => Ok(signature),
+            outcome => Err(ClientErrorKind::Custom(format!("Transaction not sent: {}", outcome)).into()),
+        }
+    }
+    /// Signs with the payer plus `signers` (authorities required by the instructions) and sends, simulates
+    /// or exports the transaction. A failed simulation is returned as the transaction error
+    pub fn process_transaction(&self, instructions: &[Instruction], signers: &[&dyn Signer]) -> ClientResult<Outcome> {
+        let mut all_instructions: Vec<Instruction> = self.get_prefix_instructions();
+        let mut all_signers: Vec<&dyn Signer> = vec![ self.payer.as_ref() ];
+        if let Some(durable_nonce) = &self.durable_nonce {
+            all_signers.push(durable_nonce.authority.as_ref());
+        }
+        all_instructions.extend_from_slice(instructions);
+        all_signers.extend_from_slice(signers);
+
+        let blockhash: Hash = self.get_blockhash()?;
+        let mut transaction: Transaction = Transaction::new_with_payer(&all_instructions, Some(&self.payer.pubkey()));
+
+        match &self.execution_mode {
+            ExecutionMode::Send => {
+                transaction.try_sign(&all_signers, blockhash)?;
+                self.send_and_confirm_with_strategy(transaction, &all_signers)
+                    .map(Outcome::Sent)
+            },
+            ExecutionMode::DryRun => {
+                transaction.try_sign(&all_signers, blockhash)?;
+                let result = self.backend.simulate_transaction(&transaction)?;
+                let report: SimulationReport = SimulationReport::new(&transaction, &self.spl_governance_program_address, result);
+                self.outcomes.borrow_mut().push(Outcome::Simulated(report.clone()));
+                match &report.error {
+                    Some(err) => Err(ClientErrorKind::TransactionError(err.clone()).into()),
+                    None => Ok(Outcome::Simulated(report)),
+                }
+            },
+            ExecutionMode::Export(directory) => {
+                // Every exported transaction advances the nonce, so one nonce value can back only one of them
+                if self.durable_nonce.is_some() && self.exported_nonce_blockhash.get() == Some(blockhash) {
+                    return Err(ClientErrorKind::Custom("Durable nonce is already used by a previously exported transaction: submit it first or use another nonce account".to_string()).into());
+                }
+                self.exported_nonce_blockhash.set(Some(blockhash));
+
+                // Signers without a private key (`NullSigner`) leave their signature empty
+                transaction.try_partial_sign(&all_signers, blockhash)?;
+                let offline_transaction: OfflineTransaction =
+                    OfflineTransaction::from_transaction(&transaction, self.durable_nonce.as_ref().map(|durable_nonce| &durable_nonce.account ));
+
+                let path: PathBuf = get_export_path(directory)?;
+                offline_transaction.save(&path)
+                    .map_err(|e| ClientErrorKind::Custom(e.to_string()) )?;
+
+                self.outcomes.borrow_mut().push(Outcome::Exported(path.clone()));
+                Ok(Outcome::Exported(path))
+            },
+        }
+    }
+    /// Instructions `process_transaction` puts before those of every transaction: `advance_nonce_account` with a durable nonce
+    pub(crate) fn get_prefix_instructions(&self) -> Vec<Instruction> {
+        self.durable_nonce.iter()
+            .map(|durable_nonce| system_instruction::advance_nonce_account(&durable_nonce.account, &durable_nonce.authority.pubkey()) )
+            .collect()
+    }
+    /// Sends and waits for the confirmation according to the current `SendStrategy`,
+    /// re-signing with a fresh blockhash before every retry (unless a durable nonce is used)
+    fn send_and_confirm_with_strategy(&self, mut transaction: Transaction, signers: &[&dyn Signer]) -> ClientResult<Signature> {
+        let send_strategy: SendStrategy = self.send_strategy.borrow().clone();
+
+        let mut attempt: u32 = 0;
+        loop {
+            let result: ClientResult<Signature> =
+                self.backend.send_and_confirm_transaction(&transaction, &send_strategy, self.durable_nonce.is_some());
+
+            match result {
+                Err(error) if attempt < send_strategy.retries && is_retryable(&error) => {
+                    attempt += 1;
+                    if let Some(on_retry) = send_strategy.on_retry {
+                        on_retry(&error, attempt);
+                    }
+                    thread::sleep(send_strategy.get_backoff(attempt));
+
+                    if self.durable_nonce.is_none() {
+                        let blockhash: Hash = self.backend.get_latest_blockhash(send_strategy.commitment)?;
+                        transaction.try_sign(signers, blockhash)?;
+                    }
+                },
+                result => return result,
+            }
+        }
+    }
+    /// Runs `f` with `send_strategy` instead of the interactor default, e.g. to confirm a single call as finalized
+    pub fn using_send_strategy<T>(&self, send_strategy: SendStrategy, f: impl FnOnce(&Self) -> T) -> T {
+        let default_send_strategy: SendStrategy = self.send_strategy.replace(send_strategy);
+        let result: T = f(self);
+        self.send_strategy.replace(default_send_strategy);
+        result
+    }
+    /// Recent blockhash, or the stored value of the durable nonce if one is set
+    fn get_blockhash(&self) -> ClientResult<Hash> {
+        match &self.durable_nonce {
+            Some(durable_nonce) => {
+                // Same commitment as sent transactions, otherwise an already advanced nonce may be read
+                let account: Account =
+                    self.get_account(&durable_nonce.account)?
+                        .ok_or_else(|| ClientErrorKind::Custom(format!("Nonce account {} does not exist", durable_nonce.account)) )?;
+                nonce_utils::data_from_account(&account)
+                    .map(|data| data.blockhash )
+                    .map_err(|e| ClientErrorKind::Custom(e.to_string()).into() )
+            },
+            None => self.backend.get_latest_blockhash(self.send_strategy.borrow().commitment),
+        }
+    }
+    /// Creates the nonce account if it does not exist yet, otherwise checks that `authority` controls it.
+    /// Must be called before `with_durable_nonce`: the creation itself uses a recent blockhash
+    pub fn setup_durable_nonce(&self, nonce_account: Arc<dyn Signer>, authority: Arc<dyn Signer>) -> ClientResult<DurableNonce> {
+        let nonce_pubkey: Pubkey = nonce_account.pubkey();
+        let authority_pubkey: Pubkey = authority.pubkey();
+
+        match self.get_account(&nonce_pubkey)? {
+            Some(account) => {
+                let data = nonce_utils::data_from_account(&account)
+                    .map_err(|e| ClientErrorKind::Custom(e.to_string()) )?;
+                if data.authority != authority_pubkey {
+                    return Err(ClientErrorKind::Custom(format!("Nonce account {} is controlled by {}, not {}", nonce_pubkey, data.authority, authority_pubkey)).into());
+                }
+            },
+            None => {
+                let lamports: u64 = self.backend.get_minimum_balance_for_rent_exemption(NonceState::size())?;
+                let create_nonce_account_instructions: Vec<Instruction> =
+                    system_instruction::create_nonce_account(
+                        &self.payer.pubkey(),
+                        &nonce_pubkey,
+                        &authority_pubkey,
+                        lamports,
+                    );
+
+                self.send_transaction(
+                        &create_nonce_account_instructions,
+                        &[
+                            nonce_account.as_ref(),
+                        ],
+                    )?;
+            },
+        }
+
+        Ok(
+            DurableNonce {
+                account: nonce_pubkey,
+                authority,
+            }
+        )
+    }
+    /// Account at the commitment of the current `SendStrategy`, `None` if it does not exist
+    pub fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>> {
+        self.backend.get_account(address, self.send_strategy.borrow().commitment)
+    }
+    fn get_account_data(&self, address: &Pubkey) -> ClientResult<Vec<u8>> {
+        self.get_account(address)?
+            .map(|account| account.data )
+            .ok_or_else(|| ClientErrorKind::Custom(format!("AccountNotFound: pubkey={}", address)).into() )
+    }
     pub fn account_exists(&self, address: &Pubkey) -> bool {
-        self.solana_client.get_account(&address).is_ok()
+        matches!(self.get_account(address), Ok(Some(_)))
+    }
+    pub fn get_clock(&self) -> ClientResult<Clock> {
+        self.backend.get_clock()
+    }
+    /// Warps the program-test bank; on a real cluster waits for the time to pass
+    pub fn advance_clock(&self, seconds: u64) -> ClientResult<Clock> {
+        self.backend.advance_clock(seconds)
+    }
+    pub fn warp_to_slot(&self, slot: Slot) -> ClientResult<Clock> {
+        self.backend.warp_to_slot(slot)
+    }
+    /// Only the program-test backend can set accounts
+    pub fn set_account(&self, address: &Pubkey, account: &Account) -> ClientResult<()> {
+        self.backend.set_account(address, account)
+    }
+    /// Only the program-test backend can set the clock
+    pub fn set_unix_timestamp(&self, unix_timestamp: UnixTimestamp) -> ClientResult<Clock> {
+        let mut clock: Clock = self.get_clock()?;
+        clock.unix_timestamp = unix_timestamp;
+        self.backend.set_clock(&clock)?;
+        Ok(clock)
+    }
+    /// Moves the clock past `max_voting_time` after the proposal voting start, so the vote can be finalized
+    pub fn advance_past_voting_end(&self, governance: &Governance, proposal: &Proposal) -> ClientResult<Clock> {
+        let voting_at: UnixTimestamp = proposal.data.voting_at
+            .ok_or_else(|| ClientErrorKind::Custom(format!("Proposal {} is not in voting", proposal.address)) )?;
+        let voting_end: UnixTimestamp = voting_at + governance.data.config.max_voting_time as UnixTimestamp;
+        self.advance_clock_to(voting_end + 1)
+    }
+    /// Moves the clock past `hold_up_time` after the proposal voting completion, so its transactions can be executed
+    pub fn advance_past_hold_up_time(&self, proposal: &Proposal, hold_up_time: u32) -> ClientResult<Clock> {
+        let proposal: Proposal = self.reload_proposal(proposal)?;
+        let voting_completed_at: UnixTimestamp = proposal.data.voting_completed_at
+            .ok_or_else(|| ClientErrorKind::Custom(format!("Voting on proposal {} is not completed", proposal.address)) )?;
+        self.advance_clock_to(voting_completed_at + hold_up_time as UnixTimestamp)
+    }
+    fn advance_clock_to(&self, unix_timestamp: UnixTimestamp) -> ClientResult<Clock> {
+        let clock: Clock = self.get_clock()?;
+        if clock.unix_timestamp >= unix_timestamp {
+            Ok(clock)
+        } else {
+            self.advance_clock((unix_timestamp - clock.unix_timestamp) as u64)
+        }
+    }
+    /// SPL token mint without freeze authority, e.g. a community mint for a new realm
+    pub fn create_mint(&self, mint_keypair: &Keypair, mint_authority_pubkey: &Pubkey, decimals: u8) -> ClientResult<Outcome> {
+        let lamports: u64 = self.backend.get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)?;
+
+        self.process_transaction(
+                &[
+                    system_instruction::create_account(
+                        &self.payer.pubkey(),
+                        &mint_keypair.pubkey(),
+                        lamports,
+                        spl_token::state::Mint::LEN as u64,
+                        &spl_token::id(),
+                    ),
+                    spl_token::instruction::initialize_mint(
+                        &spl_token::id(),
+                        &mint_keypair.pubkey(),
+                        mint_authority_pubkey,
+                        None,
+                        decimals,
+                    ).map_err(|e| ClientErrorKind::Custom(e.to_string()) )?,
+                ],
+                &[
+                    mint_keypair,
+                ],
+            )
+    }
+    pub fn get_mint_decimals(&self, mint_pubkey: &Pubkey) -> ClientResult<u8> {
+        let data: Vec<u8> = self.get_account_data(mint_pubkey)?;
+        spl_token::state::Mint::unpack(&data)
+            .map(|mint| mint.decimals )
+            .map_err(|e| ClientErrorKind::Custom(format!("Mint {}: {}", mint_pubkey, e)).into() )
+    }
+    pub fn get_program_address(&self) -> Pubkey {
+        self.spl_governance_program_address
+    }
+    /// Addin used for the voter weight records of `setup_voter_weight_record_fixed`
+    pub fn get_voter_weight_addin_address(&self) -> Pubkey {
+        self.spl_governance_voter_weight_addin_address
     }
     pub fn get_realm_address(&self, name: &str) -> Pubkey {
         get_realm_address(&self.spl_governance_program_address, name)
@@ -121,162 +573,380 @@ impl SplGovernanceInteractor {
         let proposal_index_arr: [u8; 4] = [proposal_index,0,0,0];
         get_proposal_address(&self.spl_governance_program_address, &governance_pubkey, community_mint_pubkey, &proposal_index_arr)
     }
-    pub fn get_realm_v2(&self, realm_name: &str) -> Result<RealmV2,()> {
+    pub fn get_voter_weight_record_address_fixed(&self, realm: &Realm, goverinig_token_owner: &Pubkey) -> Pubkey {
+        let (voter_weight_record_pubkey,_): (Pubkey,u8) = spl_governance_addin_fixed_weights::instruction::get_voter_weight_address(&self.spl_governance_voter_weight_addin_address, &realm.address, &realm.data.community_mint, goverinig_token_owner);
+        voter_weight_record_pubkey
+    }
+    pub fn get_realm_v2(&self, realm_name: &str) -> ClientResult<RealmV2> {
         let realm_pubkey: Pubkey = self.get_realm_address(realm_name);
 
-        self.solana_client.get_account_data(&realm_pubkey)
-            .map_err(|_|())
-            .and_then(|data|{
-                let mut data_slice: &[u8] = &data;
-                RealmV2::deserialize(&mut data_slice).map_err(|_|())
-
-            })
+        self.get_versioned_account::<RealmV2>(&realm_pubkey).map(|realm| realm.data )
     }
-    pub fn get_token_owner_record_v2(&self, goverinig_token_owner: &Pubkey, community_mint_pubkey: &Pubkey, realm_name: &str) -> TokenOwnerRecordV2 {
+    pub fn get_token_owner_record_v2(&self, goverinig_token_owner: &Pubkey, community_mint_pubkey: &Pubkey, realm_name: &str) -> ClientResult<TokenOwnerRecordV2> {
         let token_owner_record_pubkey: Pubkey = self.get_token_owner_record_address(goverinig_token_owner, community_mint_pubkey, realm_name);
 
-        let mut dt: &[u8] = &self.solana_client.get_account_data(&token_owner_record_pubkey).unwrap();
-        TokenOwnerRecordV2::deserialize(&mut dt).unwrap()
+        self.get_versioned_account::<TokenOwnerRecordV2>(&token_owner_record_pubkey).map(|token_owner_record| token_owner_record.data )
     }
-    pub fn get_governance_v2(&self, realm_name: &str, governed_account_pubkey: &Pubkey) -> GovernanceV2 {
+    pub fn get_governance_v2(&self, realm_name: &str, governed_account_pubkey: &Pubkey) -> ClientResult<GovernanceV2> {
         let governance_pubkey: Pubkey = self.get_governance_address(realm_name, governed_account_pubkey);
 
-        let mut dt: &[u8] = &self.solana_client.get_account_data(&governance_pubkey).unwrap();
-        GovernanceV2::deserialize(&mut dt).unwrap()
+        self.get_versioned_account::<GovernanceV2>(&governance_pubkey).map(|governance| governance.data )
     }
-    pub fn get_proposal_v2(&self, community_mint_pubkey: &Pubkey, realm_name: &str, governed_account_pubkey: &Pubkey, proposal_index: u8) -> ProposalV2 {
+    pub fn get_proposal_v2(&self, community_mint_pubkey: &Pubkey, realm_name: &str, governed_account_pubkey: &Pubkey, proposal_index: u8) -> ClientResult<ProposalV2> {
         let proposal_pubkey: Pubkey = self.get_proposal_address(community_mint_pubkey, realm_name, governed_account_pubkey, proposal_index);
 
-        let mut dt: &[u8] = &self.solana_client.get_account_data(&proposal_pubkey).unwrap();
-        ProposalV2::deserialize(&mut dt).unwrap()
+        self.get_proposal_data(&proposal_pubkey)
+    }
+    pub fn get_proposal_data(&self, proposal_pubkey: &Pubkey) -> ClientResult<ProposalV2> {
+        self.get_versioned_account::<ProposalV2>(proposal_pubkey).map(|proposal| proposal.data )
+    }
+    pub fn reload_proposal(&self, proposal: &Proposal) -> ClientResult<Proposal> {
+        self.get_proposal_data(&proposal.address)
+            .map(|data|
+                Proposal {
+                    address: proposal.address,
+                    data,
+                }
+            )
+    }
+    pub fn get_voter_weight_record(&self, voter_weight_record_pubkey: &Pubkey) -> ClientResult<VoterWeightRecord> {
+        let data: Vec<u8> = self.get_account_data(voter_weight_record_pubkey)?;
+        let mut dt: &[u8] = &data;
+        Ok(VoterWeightRecord::deserialize(&mut dt)?)
+    }
+    pub fn get_max_voter_weight_record(&self, max_voter_weight_record_pubkey: &Pubkey) -> ClientResult<MaxVoterWeightRecord> {
+        let data: Vec<u8> = self.get_account_data(max_voter_weight_record_pubkey)?;
+        let mut dt: &[u8] = &data;
+        Ok(MaxVoterWeightRecord::deserialize(&mut dt)?)
+    }
+    /// Weight the voter votes with: from its voter weight record, 0 without one
+    pub fn get_voter_weight(&self, token_owner: &TokenOwner) -> ClientResult<u64> {
+        match token_owner.voter_weight_record_address {
+            Some(voter_weight_record_pubkey) => Ok(self.get_voter_weight_record(&voter_weight_record_pubkey)?.voter_weight),
+            None => Ok(0),
+        }
+    }
+    /// Max vote weight of the realm from its max voter weight record, 0 without one
+    pub fn get_max_vote_weight(&self, realm: &Realm) -> ClientResult<u64> {
+        match realm.get_max_voter_weight_record_address() {
+            Some(max_voter_weight_record_pubkey) => Ok(self.get_max_voter_weight_record(&max_voter_weight_record_pubkey)?.max_voter_weight),
+            None => Ok(0),
+        }
+    }
+
+    /// Governance program accounts of `account_type` (memcmp on the first byte) also matching `filters`
+    pub fn get_program_accounts(&self, account_type: GovernanceAccountType, filters: &[AccountFilter]) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let mut all_filters: Vec<AccountFilter> = vec![ AccountFilter::new(0, &[account_type as u8]) ];
+        all_filters.extend_from_slice(filters);
+        self.backend.get_program_accounts(&self.spl_governance_program_address, &all_filters, self.send_strategy.borrow().commitment)
+    }
+    /// Accounts of every V1 and V2 account type of `T`, decoded into the V2 structure
+    fn get_program_accounts_data<T: VersionedAccount>(&self, filters: &[AccountFilter]) -> ClientResult<Vec<(Pubkey, T)>> {
+        let mut accounts: Vec<(Pubkey, T)> = Vec::new();
+        for (account_type, _) in T::get_account_types() {
+            for (address, account) in self.get_program_accounts(account_type, filters)? {
+                let versioned: Versioned<T> = decode(&self.spl_governance_program_address, &address, &account)
+                    .map_err(|e| ClientErrorKind::Custom(e.to_string()) )?;
+                accounts.push((address, versioned.data));
+            }
+        }
+        Ok(accounts)
+    }
+    /// Realm, governance, proposal, token owner record or vote record of either layout version;
+    /// fails with the account type found if it is another kind of account
+    pub fn get_versioned_account<T: VersionedAccount>(&self, address: &Pubkey) -> ClientResult<Versioned<T>> {
+        let account: Account = self.get_account(address)?
+            .ok_or_else(|| ClientErrorKind::Custom(format!("AccountNotFound: pubkey={}", address)) )?;
+        decode(&self.spl_governance_program_address, address, &account)
+            .map_err(|e| ClientErrorKind::Custom(e.to_string()).into() )
+    }
+    pub fn get_realms(&self) -> ClientResult<Vec<(Pubkey, RealmV2)>> {
+        self.get_program_accounts_data(&[])
+    }
+    /// Governances of all kinds (account, program, mint, token)
+    pub fn get_governances(&self, realm_pubkey: &Pubkey) -> ClientResult<Vec<(Pubkey, GovernanceV2)>> {
+        self.get_program_accounts_data(&[ AccountFilter::new(PARENT_ACCOUNT_OFFSET, realm_pubkey.as_ref()) ])
     }
-    pub fn get_voter_weight_record(&self, voter_weight_record_pubkey: &Pubkey) -> VoterWeightRecord {
-        let mut dt: &[u8] = &self.solana_client.get_account_data(voter_weight_record_pubkey).unwrap();
-        VoterWeightRecord::deserialize(&mut dt).unwrap()
+    pub fn get_proposals(&self, governance_pubkey: &Pubkey) -> ClientResult<Vec<(Pubkey, ProposalV2)>> {
+        self.get_program_accounts_data(&[ AccountFilter::new(PARENT_ACCOUNT_OFFSET, governance_pubkey.as_ref()) ])
     }
-    pub fn get_max_voter_weight_record(&self, max_voter_weight_record_pubkey: &Pubkey) -> MaxVoterWeightRecord {
-        let mut dt: &[u8] = &self.solana_client.get_account_data(max_voter_weight_record_pubkey).unwrap();
-        MaxVoterWeightRecord::deserialize(&mut dt).unwrap()
+    pub fn get_token_owner_records(&self, realm_pubkey: &Pubkey) -> ClientResult<Vec<(Pubkey, TokenOwnerRecordV2)>> {
+        self.get_program_accounts_data(&[ AccountFilter::new(PARENT_ACCOUNT_OFFSET, realm_pubkey.as_ref()) ])
+    }
+    /// Votes cast on the proposal: relinquishing while voting closes the record, afterwards it is only marked relinquished
+    pub fn get_vote_records(&self, proposal_pubkey: &Pubkey) -> ClientResult<Vec<(Pubkey, VoteRecordV2)>> {
+        self.get_program_accounts_data(&[ AccountFilter::new(PARENT_ACCOUNT_OFFSET, proposal_pubkey.as_ref()) ])
     }
 
-    pub fn create_realm(&self, realm_authority: Keypair, community_mint_pubkey: &Pubkey, addin_opt: Option<Pubkey>, realm_name: &str) -> Result<Realm,ClientError> {
+    pub fn create_realm(&self, realm_authority: Arc<dyn Signer>, community_mint_pubkey: &Pubkey, addin_opt: Option<Pubkey>, realm_name: &str) -> Result<Realm,ClientError> {
         let realm_pubkey: Pubkey = self.get_realm_address(realm_name);
 
         if self.account_exists(&realm_pubkey) {
-            Ok(
-                Realm {
-                    authority: realm_authority,
-                    address: realm_pubkey,
-                    data: self.get_realm_v2(realm_name).unwrap(),
-                    max_voter_weight_addin_address: addin_opt,
-                    // voter_weight_addin_address: addin_opt,
-                }
-            )
+            let realm_data: RealmV2 = self.get_realm_v2(realm_name)?;
+            let drifts: Vec<Drift> = self.get_realm_drifts(&realm_pubkey, &realm_data, &realm_authority.pubkey(), community_mint_pubkey, addin_opt);
+            self.check_drifts(drifts)
+                .map(|_|
+                    Realm {
+                        authority: realm_authority,
+                        address: realm_pubkey,
+                        data: realm_data,
+                        max_voter_weight_addin_address: addin_opt,
+                        max_voter_weight_record_address: None,
+                        // voter_weight_addin_address: addin_opt,
+                    }
+                )
+                .and_then(|realm| self.record_account(AccountKind::Realm, realm_name, &realm_pubkey).map(|_| realm ) )
         } else {
             let realm_authority_pubkey: Pubkey = realm_authority.pubkey();
+            let payer_pubkey: Pubkey = self.payer.pubkey();
 
             let create_realm_instruction: Instruction =
                 create_realm(
                     &self.spl_governance_program_address,
                     &realm_authority_pubkey,
                     community_mint_pubkey,
-                    &realm_authority_pubkey,
+                    &payer_pubkey,
                     None,
                     addin_opt,
                     addin_opt,
                     realm_name.to_string(),
                     MIN_COMMUNITY_WEIGHT_TO_CREATE_GOVERNANCE,
-                    MintMaxVoteWeightSource::SupplyFraction(10_000_000_000),
+                    COMMUNITY_MINT_MAX_VOTE_WEIGHT_SOURCE,
                     // MintMaxVoteWeightSource::FULL_SUPPLY_FRACTION,
                 );
             
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.send_transaction(
                     &[
                         create_realm_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
-                    &[
-                        &realm_authority,
-                    ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
-                .map(|_|
-                    Realm {
-                        authority: realm_authority,
-                        address: realm_pubkey,
-                        data: self.get_realm_v2(realm_name).unwrap(),
-                        max_voter_weight_addin_address: addin_opt,
-                        // voter_weight_addin_address: addin_opt,
-                    }
-                )
-                // .map_err(|_|())
+                    &[],
+                )?;
+            let realm: Realm =
+                Realm {
+                    authority: realm_authority,
+                    address: realm_pubkey,
+                    data: self.get_realm_v2(realm_name)?,
+                    max_voter_weight_addin_address: addin_opt,
+                    max_voter_weight_record_address: None,
+                    // voter_weight_addin_address: addin_opt,
+                };
+            self.record_account(AccountKind::Realm, realm_name, &realm_pubkey)?;
+            Ok(realm)
         }
     }
 
-    pub fn create_token_owner_record(&self, realm: &Realm, token_owner_keypair: Keypair) -> Result<TokenOwner,()> {
-        let token_owner_pubkey: Pubkey = token_owner_keypair.pubkey();
-        let token_owner_record_pubkey: Pubkey = self.get_token_owner_record_address(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name);
+    /// Differences between an existing realm and the parameters of `create_realm`
+    fn get_realm_drifts(&self, realm_pubkey: &Pubkey, realm_data: &RealmV2, realm_authority_pubkey: &Pubkey, community_mint_pubkey: &Pubkey, addin_opt: Option<Pubkey>) -> Vec<Drift> {
+        let mut drifts: Vec<Drift> =
+            DriftCheck::new(AccountKind::Realm, realm_pubkey)
+                .compare("authority", &Some(*realm_authority_pubkey), &realm_data.authority)
+                .compare("community_mint", community_mint_pubkey, &realm_data.community_mint)
+                .compare("min_community_weight_to_create_governance", &MIN_COMMUNITY_WEIGHT_TO_CREATE_GOVERNANCE, &realm_data.config.min_community_weight_to_create_governance)
+                .compare("community_mint_max_vote_weight_source", &COMMUNITY_MINT_MAX_VOTE_WEIGHT_SOURCE, &realm_data.config.community_mint_max_vote_weight_source)
+                .compare("use_community_voter_weight_addin", &addin_opt.is_some(), &realm_data.config.use_community_voter_weight_addin)
+                .compare("use_max_community_voter_weight_addin", &addin_opt.is_some(), &realm_data.config.use_max_community_voter_weight_addin)
+                .into_drifts();
 
-        if self.account_exists(&token_owner_record_pubkey) {
-            Ok(
-                TokenOwner {
-                    authority: token_owner_keypair,
-                    token_owner_record_address: token_owner_record_pubkey,
-                    token_owner_record: self.get_token_owner_record_v2(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name),
-                    // voter_weight_record_authority: None,
-                    voter_weight_record_address: None,
-                    // voter_weight_record: None,
+        if let Some(realm_config) = self.get_realm_config(realm_pubkey) {
+            drifts.extend(
+                DriftCheck::new(AccountKind::Realm, &get_realm_config_address(&self.spl_governance_program_address, realm_pubkey))
+                    .compare("community_voter_weight_addin", &addin_opt, &realm_config.community_voter_weight_addin)
+                    .compare("max_community_voter_weight_addin", &addin_opt, &realm_config.max_community_voter_weight_addin)
+                    .into_drifts()
+            );
+        }
+        drifts
+    }
+
+    /// Addin addresses of the realm: the realm config account only exists for realms with addins
+    pub fn get_realm_config(&self, realm_pubkey: &Pubkey) -> Option<RealmConfigAccount> {
+        let realm_config_pubkey: Pubkey = get_realm_config_address(&self.spl_governance_program_address, realm_pubkey);
+        let data: Vec<u8> = self.get_account_data(&realm_config_pubkey).ok()?;
+        let mut dt: &[u8] = &data;
+        RealmConfigAccount::deserialize(&mut dt).ok()
+    }
+
+    /// Existing realm, without creating it or checking it for drift
+    pub fn get_realm(&self, realm_authority: Arc<dyn Signer>, realm_name: &str, addin_opt: Option<Pubkey>) -> ClientResult<Realm> {
+        self.get_realm_v2(realm_name)
+            .map(|realm_data|
+                Realm {
+                    authority: realm_authority,
+                    address: self.get_realm_address(realm_name),
+                    data: realm_data,
+                    max_voter_weight_addin_address: addin_opt,
+                    max_voter_weight_record_address: None,
                 }
             )
+    }
+
+    /// Signed by the realm authority
+    pub fn set_realm_config(&self, realm: &Realm, community_voter_weight_addin: Option<Pubkey>, max_community_voter_weight_addin: Option<Pubkey>,
+                            min_community_weight_to_create_governance: u64, community_mint_max_vote_weight_source: MintMaxVoteWeightSource) -> ClientResult<Outcome> {
+        let set_realm_config_instruction: Instruction =
+            set_realm_config(
+                &self.spl_governance_program_address,
+                &realm.address,
+                &realm.authority.pubkey(),
+                realm.data.config.council_mint,
+                &self.payer.pubkey(),
+                community_voter_weight_addin,
+                max_community_voter_weight_addin,
+                min_community_weight_to_create_governance,
+                community_mint_max_vote_weight_source,
+            );
+
+        self.process_transaction(
+                &[
+                    set_realm_config_instruction,
+                ],
+                &[
+                    realm.authority.as_ref(),
+                ],
+            )
+    }
+
+    pub fn create_token_owner_record_instruction(&self, realm: &Realm, token_owner_pubkey: &Pubkey) -> Instruction {
+        create_token_owner_record(
+            &self.spl_governance_program_address,
+            &realm.address,
+            token_owner_pubkey,
+            &realm.data.community_mint,
+            &self.payer.pubkey(),
+        )
+    }
+
+    pub fn create_token_owner_record(&self, realm: &Realm, token_owner: Arc<dyn Signer>) -> ClientResult<TokenOwner> {
+        let token_owner_pubkey: Pubkey = token_owner.pubkey();
+        let token_owner_record_pubkey: Pubkey = self.get_token_owner_record_address(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name);
+
+        if self.account_exists(&token_owner_record_pubkey) {
+            // Nothing to drift: realm, mint and owner are the seeds of the address, the delegate is not requested here
+            self.get_token_owner_record_v2(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name)
+                .map(|token_owner_record|
+                    TokenOwner {
+                        authority: token_owner,
+                        token_owner_record_address: token_owner_record_pubkey,
+                        token_owner_record,
+                        // voter_weight_record_authority: None,
+                        voter_weight_record_address: None,
+                        // voter_weight_record: None,
+                    }
+                )
+                .and_then(|token_owner| self.record_account(AccountKind::TokenOwnerRecord, &self.get_label(&token_owner_pubkey), &token_owner_record_pubkey).map(|_| token_owner ) )
         } else {
-            let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
-        
-            let create_token_owner_record_instruction: Instruction =
-                create_token_owner_record(
-                    &self.spl_governance_program_address,
-                    &realm.address,
-                    &token_owner_pubkey,
-                    &realm.data.community_mint,
-                    &realm_authority_pubkey,
-                );
+            let create_token_owner_record_instruction: Instruction = self.create_token_owner_record_instruction(realm, &token_owner_pubkey);
             
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.send_transaction(
                     &[
                         create_token_owner_record_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
-                    &[
-                        &realm.authority,
-                    ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
-                .map(|_|
+                    &[],
+                )
+                .and_then(|_| self.get_token_owner_record_v2(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name) )
+                .map(|token_owner_record|
                     TokenOwner {
-                        authority: token_owner_keypair,
+                        authority: token_owner,
                         token_owner_record_address: token_owner_record_pubkey,
-                        token_owner_record: self.get_token_owner_record_v2(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name),
+                        token_owner_record,
                         // voter_weight_record_authority: None,
                         voter_weight_record_address: None,
                         // voter_weight_record: None,
                     }
                 )
-                .map_err(|_|())
+                .and_then(|token_owner| self.record_account(AccountKind::TokenOwnerRecord, &self.get_label(&token_owner_pubkey), &token_owner_record_pubkey).map(|_| token_owner ) )
         }
     }
 
-    pub fn _setup_max_voter_weight_record_mock(&self, realm: &Realm, max_voter_weight_record_keypair: Keypair, max_voter_weight: u64) -> Result<Signature,()> {
+    pub fn set_governance_delegate(&self, realm: &Realm, token_owner: &TokenOwner, new_governance_delegate: Option<Pubkey>) -> ClientResult<Outcome> {
+        let governance_authority_pubkey: Pubkey = token_owner.authority.pubkey();
+
+        let set_governance_delegate_instruction: Instruction =
+            set_governance_delegate(
+                &self.spl_governance_program_address,
+                &governance_authority_pubkey,
+                &realm.address,
+                &realm.data.community_mint,
+                &token_owner.get_owner_pubkey(),
+                &new_governance_delegate,
+            );
+        
+        self.process_transaction(
+                &[
+                    set_governance_delegate_instruction,
+                ],
+                &[
+                    token_owner.authority.as_ref(),
+                ],
+            )
+    }
+
+    /// Existing token owner record of `token_owner` (e.g. created by a batch) with its fixed weights voter weight record, if any
+    pub fn get_token_owner(&self, realm: &Realm, token_owner: Arc<dyn Signer>) -> Result<TokenOwner,()> {
+        let token_owner_pubkey: Pubkey = token_owner.pubkey();
+        let token_owner_record_pubkey: Pubkey = self.get_token_owner_record_address(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name);
+
+        if !self.account_exists(&token_owner_record_pubkey) {
+            return Err(());
+        }
+
+        let voter_weight_record_pubkey: Pubkey = self.get_voter_weight_record_address_fixed(realm, &token_owner_pubkey);
+
+        Ok(
+            TokenOwner {
+                authority: token_owner,
+                token_owner_record_address: token_owner_record_pubkey,
+                token_owner_record: self.get_token_owner_record_v2(&token_owner_pubkey, &realm.data.community_mint, &realm.data.name).map_err(|_|())?,
+                voter_weight_record_address:
+                    if self.account_exists(&voter_weight_record_pubkey) {
+                        Some(voter_weight_record_pubkey)
+                    } else {
+                        None
+                    },
+            }
+        )
+    }
+
+    /// Token owner acting through its governance delegate: the delegate becomes `authority`
+    /// and is used to sign proposals, sign-offs and votes on behalf of `token_owner_pubkey`
+    pub fn get_delegated_token_owner(&self, realm: &Realm, token_owner_pubkey: &Pubkey, delegate: Arc<dyn Signer>) -> Result<TokenOwner,()> {
+        let token_owner_record_pubkey: Pubkey = self.get_token_owner_record_address(token_owner_pubkey, &realm.data.community_mint, &realm.data.name);
+
+        if !self.account_exists(&token_owner_record_pubkey) {
+            return Err(());
+        }
+
+        let token_owner_record: TokenOwnerRecordV2 = self.get_token_owner_record_v2(token_owner_pubkey, &realm.data.community_mint, &realm.data.name).map_err(|_|())?;
+        if token_owner_record.governance_delegate != Some(delegate.pubkey()) {
+            return Err(());
+        }
+
+        let (voter_weight_record_pubkey,_): (Pubkey,u8) = spl_governance_addin_fixed_weights::instruction::get_voter_weight_address(&self.spl_governance_voter_weight_addin_address, &realm.address, &realm.data.community_mint, token_owner_pubkey);
+
+        Ok(
+            TokenOwner {
+                authority: delegate,
+                token_owner_record_address: token_owner_record_pubkey,
+                token_owner_record: token_owner_record,
+                voter_weight_record_address:
+                    if self.account_exists(&voter_weight_record_pubkey) {
+                        Some(voter_weight_record_pubkey)
+                    } else {
+                        None
+                    },
+            }
+        )
+    }
+
+    /// `None` if the record already exists
+    pub fn setup_max_voter_weight_record_mock(&self, realm: &Realm, max_voter_weight_record_keypair: Keypair, max_voter_weight: u64) -> ClientResult<Option<Outcome>> {
         let max_voter_weight_record_pubkey: Pubkey = max_voter_weight_record_keypair.pubkey();
 
         if self.account_exists(&max_voter_weight_record_pubkey) {
-            Err(())
+            self.record_account(AccountKind::MaxVoterWeightRecord, &realm.data.name, &max_voter_weight_record_pubkey)?;
+            Ok(None)
         } else {
-            let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
+            let payer_pubkey: Pubkey = self.payer.pubkey();
 
             let setup_max_voter_weight_record_instruction: Instruction =
                 setup_max_voter_weight_record(
@@ -284,71 +954,60 @@ impl SplGovernanceInteractor {
                     &realm.address,
                     &realm.data.community_mint,
                     &max_voter_weight_record_pubkey,
-                    &realm_authority_pubkey,
+                    &payer_pubkey,
                     max_voter_weight,
                     None,
                 );
             
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.process_transaction(
                     &[
                         setup_max_voter_weight_record_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
                     &[
-                        &realm.authority,
                         &max_voter_weight_record_keypair,
                     ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
-                .map_err(|_|())
+                )
+                .and_then(|outcome| self.record_account(AccountKind::MaxVoterWeightRecord, &realm.data.name, &max_voter_weight_record_pubkey).map(|_| Some(outcome) ) )
         }
     }
 
     // pub fn setup_max_voter_weight_record_fixed(&self, voter_weight_addin_autority: &Keypair, realm: &Realm, max_voter_weight: u64) -> ClientResult<Signature> {
-    pub fn setup_max_voter_weight_record_fixed(&self, realm: &Realm) -> Result<Signature,()> {
+    /// `None` if the record already exists
+    pub fn setup_max_voter_weight_record_fixed(&self, realm: &Realm) -> ClientResult<Option<Outcome>> {
         // let max_voter_weight_record_pubkey: Pubkey = max_voter_weight_record_keypair.pubkey();
         let (max_voter_weight_record_pubkey,_): (Pubkey,u8) = spl_governance_addin_fixed_weights::instruction::get_max_voter_weight_address(&self.spl_governance_voter_weight_addin_address, &realm.address, &realm.data.community_mint);
 
         if self.account_exists(&max_voter_weight_record_pubkey) {
-            Err(())
+            self.record_account(AccountKind::MaxVoterWeightRecord, &realm.data.name, &max_voter_weight_record_pubkey)?;
+            Ok(None)
         } else {
-            let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
+            let payer_pubkey: Pubkey = self.payer.pubkey();
 
             let setup_max_voter_weight_record_instruction: Instruction =
                 spl_governance_addin_fixed_weights::instruction::setup_max_voter_weight_record(
                     &self.spl_governance_voter_weight_addin_address,
                     &realm.address,
                     &realm.data.community_mint,
-                    &realm_authority_pubkey,
+                    &payer_pubkey,
                     // max_voter_weight,
                 );
             
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.process_transaction(
                     &[
                         setup_max_voter_weight_record_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
-                    &[
-                        &realm.authority,
-                        // voter_weight_addin_autority,
-                        // &max_voter_weight_record_keypair,
-                    ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
-                .map_err(|_|())
+                    &[],
+                )
+                .and_then(|outcome| self.record_account(AccountKind::MaxVoterWeightRecord, &realm.data.name, &max_voter_weight_record_pubkey).map(|_| Some(outcome) ) )
         }
     }
 
-    pub fn _setup_voter_weight_record_mock(&self, realm: &Realm, token_owner: TokenOwner, voter_weight_record_keypair: Keypair, voter_weight: u64) -> Result<TokenOwner,()> {
+    /// The record stops being accepted by the governance program after `voter_weight_expiry` slot
+    pub fn setup_voter_weight_record_mock(&self, realm: &Realm, token_owner: TokenOwner, voter_weight_record_keypair: Keypair, voter_weight: u64, voter_weight_expiry: Option<Slot>) -> ClientResult<TokenOwner> {
         let voter_weight_record_pubkey: Pubkey = voter_weight_record_keypair.pubkey();
 
         if self.account_exists(&voter_weight_record_pubkey) {
+            self.record_account(AccountKind::VoterWeightRecord, &self.get_label(&token_owner.get_owner_pubkey()), &voter_weight_record_pubkey)?;
             Ok(
                 TokenOwner {
                     authority: token_owner.authority,
@@ -360,36 +1019,30 @@ impl SplGovernanceInteractor {
                 }
             )
         } else {
-            let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
+            let payer_pubkey: Pubkey = self.payer.pubkey();
 
             let setup_voter_weight_record_instruction: Instruction =
                 setup_voter_weight_record(
                     &self.spl_governance_voter_weight_addin_address,
                     &realm.address,
                     &realm.data.community_mint,
-                    &token_owner.authority.pubkey(),
+                    &token_owner.get_owner_pubkey(),
                     &voter_weight_record_pubkey,
-                    &realm_authority_pubkey,
+                    &payer_pubkey,
                     voter_weight,
-                    None,
+                    voter_weight_expiry,
                     None,
                     None,
                 );
             
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.send_transaction(
                     &[
                         setup_voter_weight_record_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
                     &[
-                        &realm.authority,
                         &voter_weight_record_keypair,
                     ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
+                )
                 .map(|_|
                     TokenOwner {
                         authority: token_owner.authority,
@@ -400,15 +1053,26 @@ impl SplGovernanceInteractor {
                         // voter_weight_record: Some(self.get_voter_weight_record(&voter_weight_record_pubkey)),
                     }
                 )
-                .map_err(|_|())
+                .and_then(|token_owner| self.record_account(AccountKind::VoterWeightRecord, &self.get_label(&token_owner.get_owner_pubkey()), &voter_weight_record_pubkey).map(|_| token_owner ) )
         }
     }
 
-    pub fn setup_voter_weight_record_fixed(&self, realm: &Realm, token_owner: TokenOwner) -> Result<TokenOwner,()> {
-        let token_owner_pubkey: Pubkey = token_owner.authority.pubkey();
-        let (voter_weight_record_pubkey,_): (Pubkey,u8) = spl_governance_addin_fixed_weights::instruction::get_voter_weight_address(&self.spl_governance_voter_weight_addin_address, &realm.address, &realm.data.community_mint, &token_owner_pubkey);
+    pub fn setup_voter_weight_record_fixed_instruction(&self, realm: &Realm, token_owner_pubkey: &Pubkey) -> Instruction {
+        spl_governance_addin_fixed_weights::instruction::setup_voter_weight_record(
+            &self.spl_governance_voter_weight_addin_address,
+            &realm.address,
+            &realm.data.community_mint,
+            token_owner_pubkey,
+            &self.payer.pubkey(),
+        )
+    }
+
+    pub fn setup_voter_weight_record_fixed(&self, realm: &Realm, token_owner: TokenOwner) -> ClientResult<TokenOwner> {
+        let token_owner_pubkey: Pubkey = token_owner.get_owner_pubkey();
+        let voter_weight_record_pubkey: Pubkey = self.get_voter_weight_record_address_fixed(realm, &token_owner_pubkey);
 
         if self.account_exists(&voter_weight_record_pubkey) {
+            self.record_account(AccountKind::VoterWeightRecord, &self.get_label(&token_owner_pubkey), &voter_weight_record_pubkey)?;
             Ok(
                 TokenOwner {
                     authority: token_owner.authority,
@@ -420,31 +1084,14 @@ impl SplGovernanceInteractor {
                 }
             )
         } else {
-            let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
-
-            let setup_voter_weight_record_instruction: Instruction =
-                spl_governance_addin_fixed_weights::instruction::setup_voter_weight_record(
-                    &self.spl_governance_voter_weight_addin_address,
-                    &realm.address,
-                    &realm.data.community_mint,
-                    &token_owner_pubkey,
-                    &realm_authority_pubkey,
-                );
+            let setup_voter_weight_record_instruction: Instruction = self.setup_voter_weight_record_fixed_instruction(realm, &token_owner_pubkey);
             
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.send_transaction(
                     &[
                         setup_voter_weight_record_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
-                    &[
-                        &realm.authority,
-                        // &voter_weight_record_keypair,
-                    ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
+                    &[],
+                )
                 .map(|_|
                     TokenOwner {
                         authority: token_owner.authority,
@@ -455,7 +1102,7 @@ impl SplGovernanceInteractor {
                         // voter_weight_record: Some(self.get_voter_weight_record(&voter_weight_record_pubkey)),
                     }
                 )
-                .map_err(|_|())
+                .and_then(|token_owner| self.record_account(AccountKind::VoterWeightRecord, &self.get_label(&token_owner_pubkey), &voter_weight_record_pubkey).map(|_| token_owner ) )
         }
     }
 
@@ -463,14 +1110,22 @@ impl SplGovernanceInteractor {
         let governance_pubkey: Pubkey = self.get_governance_address(&realm.data.name, governed_account_pubkey);
 
         if self.account_exists(&governance_pubkey) {
-            Ok(
-                Governance {
-                    address: governance_pubkey,
-                    data: self.get_governance_v2(&realm.data.name, governed_account_pubkey)
-                }
-            )
+            let governance_data: GovernanceV2 = self.get_governance_v2(&realm.data.name, governed_account_pubkey)?;
+            let drifts: Vec<Drift> =
+                DriftCheck::new(AccountKind::Governance, &governance_pubkey)
+                    .compare("config", &gov_config, &governance_data.config)
+                    .into_drifts();
+            self.check_drifts(drifts)
+                .map(|_|
+                    Governance {
+                        address: governance_pubkey,
+                        data: governance_data,
+                    }
+                )
+                .and_then(|governance| self.record_account(AccountKind::Governance, &self.get_label(governed_account_pubkey), &governance_pubkey).map(|_| governance ) )
         } else {
             let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
+            let payer_pubkey: Pubkey = self.payer.pubkey();
             // let token_owner_record_pubkey: Pubkey = self.get_token_owner_record_address(&realm_authority_pubkey, &community_mint_pubkey, realm_name);
 
             let create_governance_instruction: Instruction =
@@ -479,40 +1134,61 @@ impl SplGovernanceInteractor {
                     &realm.address,
                     Some(governed_account_pubkey),
                     &token_owner.token_owner_record_address,
-                    &realm_authority_pubkey,
+                    &payer_pubkey,
                     &realm_authority_pubkey,
                     token_owner.voter_weight_record_address,
                     gov_config,
                 );
             
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.send_transaction(
                     &[
                         create_governance_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
                     &[
-                        &realm.authority,
+                        realm.authority.as_ref(),
                     ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
-                .map(|_|
+                )
+                .and_then(|_| self.get_governance_v2(&realm.data.name, governed_account_pubkey) )
+                .map(|governance_data|
                     Governance {
                         address: governance_pubkey,
-                        data: self.get_governance_v2(&realm.data.name, governed_account_pubkey)
+                        data: governance_data,
                     }
                 )
+                .and_then(|governance| self.record_account(AccountKind::Governance, &self.get_label(governed_account_pubkey), &governance_pubkey).map(|_| governance ) )
                 // .map_err(|_|())
         }
     }
 
+    /// Existing governance of `governed_account_pubkey` with its current data, `None` if it does not exist
+    pub fn get_governance(&self, realm: &Realm, governed_account_pubkey: &Pubkey) -> ClientResult<Option<Governance>> {
+        let governance_pubkey: Pubkey = self.get_governance_address(&realm.data.name, governed_account_pubkey);
+        if !self.account_exists(&governance_pubkey) {
+            return Ok(None);
+        }
+        self.get_governance_v2(&realm.data.name, governed_account_pubkey)
+            .map(|governance_data|
+                Some(
+                    Governance {
+                        address: governance_pubkey,
+                        data: governance_data,
+                    }
+                )
+            )
+    }
+
     pub fn create_proposal(&self, realm: &Realm, token_owner: &TokenOwner, governance: &Governance, proposal_name: &str, proposal_description: &str, proposal_index: u32) -> Result<Proposal,ClientError> {
+        self.create_proposal_with_options(realm, token_owner, governance, proposal_name, proposal_description, proposal_index, VoteType::SingleChoice, vec!["Yes".to_string()], true)
+    }
+
+    /// `VoteType::MultiChoice(n)` lets a voter approve up to `n` of `options`
+    pub fn create_proposal_with_options(&self, realm: &Realm, token_owner: &TokenOwner, governance: &Governance, proposal_name: &str, proposal_description: &str, proposal_index: u32,
+                                        vote_type: VoteType, options: Vec<String>, use_deny_option: bool) -> Result<Proposal,ClientError> {
         let proposal_address: Pubkey = self.get_proposal_address(&realm.data.community_mint, &realm.data.name, &governance.data.governed_account, proposal_index as u8);
 
         if self.account_exists(&proposal_address) {
-            let proposal_v2: ProposalV2 = self.get_proposal_v2(&realm.data.community_mint, &realm.data.name, &governance.data.governed_account, proposal_index as u8);
+            let proposal_v2: ProposalV2 = self.get_proposal_v2(&realm.data.community_mint, &realm.data.name, &governance.data.governed_account, proposal_index as u8)?;
+            self.record_account(AccountKind::Proposal, &format!("{} {}", self.get_label(&governance.data.governed_account), proposal_index), &proposal_address)?;
             Ok(
                 Proposal {
                     address: proposal_address,
@@ -520,51 +1196,48 @@ impl SplGovernanceInteractor {
                 }
             )
         } else {
-            let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
+            let payer_pubkey: Pubkey = self.payer.pubkey();
+            let governance_authority_pubkey: Pubkey = token_owner.authority.pubkey();
 
             let create_proposal_instruction: Instruction =
                 create_proposal(
                     &self.spl_governance_program_address,
                     &governance.address,
                     &token_owner.token_owner_record_address,
-                    &realm_authority_pubkey,
-                    &realm_authority_pubkey,
+                    &governance_authority_pubkey,
+                    &payer_pubkey,
                     token_owner.voter_weight_record_address,
                     &realm.address,
                     proposal_name.to_string(),
                     proposal_description.to_string(),
                     &realm.data.community_mint,
-                    VoteType::SingleChoice,
-                    vec!["Yes".to_string()],
-                    true,
+                    vote_type,
+                    options,
+                    use_deny_option,
                     proposal_index,
                 );
 
-            let transaction: Transaction =
-                Transaction::new_signed_with_payer(
+            self.send_transaction(
                     &[
                         create_proposal_instruction,
                     ],
-                    Some(&realm_authority_pubkey),
                     &[
-                        &realm.authority,
+                        token_owner.authority.as_ref(),
                     ],
-                    self.solana_client.get_latest_blockhash().unwrap(),
-                );
-            
-            self.solana_client.send_and_confirm_transaction(&transaction)
-                .map(|_| {
-                    let proposal_v2: ProposalV2 = self.get_proposal_v2(&realm.data.community_mint, &realm.data.name, &governance.data.governed_account, proposal_index as u8);
+                )
+                .and_then(|_| self.get_proposal_v2(&realm.data.community_mint, &realm.data.name, &governance.data.governed_account, proposal_index as u8) )
+                .map(|proposal_v2|
                     Proposal {
                         address: proposal_address,
                         data: proposal_v2,
                     }
-                })
+                )
+                .and_then(|proposal| self.record_account(AccountKind::Proposal, &format!("{} {}", self.get_label(&governance.data.governed_account), proposal_index), &proposal_address).map(|_| proposal ) )
         }
     }
 
     pub fn sign_off_proposal(&self, realm: &Realm, governance: &Governance, proposal: Proposal, token_owner: &TokenOwner) -> Result<Proposal,ClientError> {
-        let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
+        let signatory_pubkey: Pubkey = token_owner.authority.pubkey();
 
         let sign_off_proposal_instruction: Instruction =
             sign_off_proposal(
@@ -572,127 +1245,300 @@ impl SplGovernanceInteractor {
                 &realm.address,
                 &governance.address,
                 &proposal.address,
-                &realm_authority_pubkey,
+                &signatory_pubkey,
                 Some(&token_owner.token_owner_record_address),
             );
         
-        let transaction: Transaction =
-            Transaction::new_signed_with_payer(
+        self.send_transaction(
                 &[
                     sign_off_proposal_instruction,
                 ],
-                Some(&realm_authority_pubkey),
                 &[
-                    &realm.authority,
+                    token_owner.authority.as_ref(),
                 ],
-                self.solana_client.get_latest_blockhash().unwrap(),
-            );
-        
-        self.solana_client.send_and_confirm_transaction(&transaction)
-            .map(|_|
-                  Proposal {
-                      address: proposal.address,
-                      data: self.get_proposal_v2(&realm.data.community_mint, &realm.data.name, &governance.data.governed_account, governance.data.proposals_count as u8),
-                  }
             )
+            .and_then(|_| self.reload_proposal(&proposal) )
     }
 
-    pub fn _add_signatory(&self, realm: &Realm, _governance: &Governance, proposal: &Proposal, token_owner: &TokenOwner) -> Result<Signature,ClientError> {
-        let realm_authority_pubkey: Pubkey = realm.authority.pubkey();
-        // let signatory_record_address = get_signatory_record_address(&self.spl_governance_program_address, &proposal.address, &token_owner.authority.pubkey());
-
+    /// Adds `signatory` to the proposal of `token_owner` (its owner or delegate signs); the proposal then
+    /// needs the sign-off of every added signatory instead of its owner's
+    pub fn add_signatory(&self, proposal: &Proposal, token_owner: &TokenOwner, signatory: &Pubkey) -> ClientResult<Outcome> {
         let add_signatory_instruction: Instruction =
             add_signatory(
                 &self.spl_governance_program_address,
                 &proposal.address,
                 &token_owner.token_owner_record_address,
-                &realm_authority_pubkey,
-                &realm_authority_pubkey,
                 &token_owner.authority.pubkey(),
+                &self.payer.pubkey(),
+                signatory,
             );
-        
-        let transaction: Transaction =
-            Transaction::new_signed_with_payer(
+
+        self.process_transaction(
                 &[
                     add_signatory_instruction,
                 ],
-                Some(&realm_authority_pubkey),
                 &[
-                    &realm.authority,
+                    token_owner.authority.as_ref(),
                 ],
-                self.solana_client.get_latest_blockhash().unwrap(),
-            );
-        
-        self.solana_client.send_and_confirm_transaction(&transaction)
-            // .map(|_|
-            //       Proposal {
-            //           address: proposal.address,
-            //           data: self.get_proposal_v2(&realm.data.community_mint, &realm.data.name, &governance.data.governed_account, governance.data.proposals_count as u8),
-            //       }
-            // )
-    }
-
-    pub fn cast_vote(&self, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner, vote_yes_no: bool) -> ClientResult<Signature> {
-        let voter_authority_pubkey: Pubkey = voter.authority.pubkey();
-        let max_voter_weight_addin_address: &Pubkey = &realm.max_voter_weight_addin_address.unwrap();
-        let (max_voter_weight_record_address,_) = get_max_voter_weight_address(max_voter_weight_addin_address, &realm.address, &realm.data.community_mint);
-
-        let vote: Vote =
-            if vote_yes_no {
-                Vote::Approve(vec![
-                    VoteChoice {
-                        rank: 0,
-                        weight_percentage: 100,
-                    }
-                ])
-            } else {
-                Vote::Deny
-            };
-        
-        let cast_vote_instruction: Instruction =
-            cast_vote(
+            )
+    }
+
+    /// Can only be executed by the governance itself, i.e. as a proposal transaction
+    pub fn set_governance_config_instruction(&self, governance: &Governance, gov_config: GovernanceConfig) -> Instruction {
+        set_governance_config(
+            &self.spl_governance_program_address,
+            &governance.address,
+            gov_config,
+        )
+    }
+
+    /// Ends the voting once `max_voting_time` has passed; the outcome is in the reloaded proposal
+    pub fn finalize_vote(&self, realm: &Realm, governance: &Governance, proposal: &Proposal) -> ClientResult<Outcome> {
+        let finalize_vote_instruction: Instruction =
+            finalize_vote(
                 &self.spl_governance_program_address,
                 &realm.address,
                 &governance.address,
                 &proposal.address,
                 &proposal.data.token_owner_record,
-                &voter.token_owner_record_address,
-                &voter_authority_pubkey,
                 &realm.data.community_mint,
-                &voter_authority_pubkey,
-                voter.voter_weight_record_address,
-                Some(max_voter_weight_record_address),
-                vote,
+                realm.get_max_voter_weight_record_address(),
+            );
+
+        self.process_transaction(
+                &[
+                    finalize_vote_instruction,
+                ],
+                &[],
+            )
+    }
+
+    /// Adds `instructions` as transaction `index` of the first option of a draft proposal, returns the proposal transaction address
+    pub fn insert_transaction(&self, governance: &Governance, proposal: &Proposal, token_owner: &TokenOwner, index: u16, hold_up_time: u32, instructions: Vec<Instruction>) -> ClientResult<Pubkey> {
+        let option_index: u8 = 0;
+        let proposal_transaction_address: Pubkey = get_proposal_transaction_address(&self.spl_governance_program_address, &proposal.address, &option_index.to_le_bytes(), &index.to_le_bytes());
+
+        let insert_transaction_instruction: Instruction =
+            insert_transaction(
+                &self.spl_governance_program_address,
+                &governance.address,
+                &proposal.address,
+                &token_owner.token_owner_record_address,
+                &token_owner.authority.pubkey(),
+                &self.payer.pubkey(),
+                option_index,
+                index,
+                hold_up_time,
+                instructions.into_iter().map(InstructionData::from).collect(),
             );
+
+        self.send_transaction(
+                &[
+                    insert_transaction_instruction,
+                ],
+                &[
+                    token_owner.authority.as_ref(),
+                ],
+            )
+            .and_then(|_| {
+                let suffix: String = format!("{} {}", self.get_proposal_label(&proposal.address), index);
+                self.record_account(AccountKind::ProposalTransaction, &suffix, &proposal_transaction_address)
+                    .map(|_| proposal_transaction_address )
+            })
+    }
+
+    /// Stored transactions of every option of the proposal, in option and index order
+    pub fn get_proposal_transactions(&self, proposal_pubkey: &Pubkey, proposal: &ProposalV2) -> ClientResult<Vec<ProposalTransactionV2>> {
+        let mut proposal_transactions: Vec<ProposalTransactionV2> = Vec::new();
+        for (option_index, option) in proposal.options.iter().enumerate() {
+            for index in 0..option.transactions_next_index {
+                let proposal_transaction_address: Pubkey = get_proposal_transaction_address(&self.spl_governance_program_address, proposal_pubkey, &(option_index as u8).to_le_bytes(), &index.to_le_bytes());
+                // Removed transactions leave gaps in the indexes
+                if !self.account_exists(&proposal_transaction_address) {
+                    continue;
+                }
+                let mut dt: &[u8] = &self.get_account_data(&proposal_transaction_address)?;
+                proposal_transactions.push(ProposalTransactionV2::deserialize(&mut dt)?);
+            }
+        }
+        Ok(proposal_transactions)
+    }
+
+    /// Executes the stored instructions of a proposal transaction, signed by the governance
+    pub fn execute_transaction(&self, governance: &Governance, proposal: &Proposal, proposal_transaction_address: &Pubkey) -> ClientResult<Outcome> {
+        let mut dt: &[u8] = &self.get_account_data(proposal_transaction_address)?;
+        let proposal_transaction: ProposalTransactionV2 = ProposalTransactionV2::deserialize(&mut dt)?;
+
+        let execute_transaction_instructions: Vec<Instruction> =
+            proposal_transaction.instructions.iter()
+                .map(|instruction_data| {
+                    let instruction: Instruction = Instruction::from(instruction_data);
+                    // The governance signs by invoke_signed inside the program, not in the transaction
+                    let instruction_accounts: Vec<AccountMeta> =
+                        instruction.accounts.into_iter()
+                            .map(|account_meta| AccountMeta { is_signer: account_meta.is_signer && account_meta.pubkey != governance.address, ..account_meta } )
+                            .collect();
+                    execute_transaction(
+                        &self.spl_governance_program_address,
+                        &governance.address,
+                        &proposal.address,
+                        proposal_transaction_address,
+                        &instruction.program_id,
+                        &instruction_accounts,
+                    )
+                })
+                .collect();
+
+        self.process_transaction(&execute_transaction_instructions, &[])
+    }
+
+    pub fn cast_vote(&self, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner, vote_yes_no: bool) -> ClientResult<Outcome> {
+        self.cast_vote_with(realm, governance, proposal, voter, yes_no_vote(vote_yes_no))
+    }
+
+    /// One `VoteChoice` per proposal option, in the order the options were created
+    pub fn cast_vote_choices(&self, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner, choices: Vec<VoteChoice>) -> ClientResult<Outcome> {
+        self.cast_vote_with(realm, governance, proposal, voter, Vote::Approve(choices))
+    }
+
+    pub fn cast_vote_instruction(&self, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner, vote: Vote) -> Instruction {
+        cast_vote(
+            &self.spl_governance_program_address,
+            &realm.address,
+            &governance.address,
+            &proposal.address,
+            &proposal.data.token_owner_record,
+            &voter.token_owner_record_address,
+            &voter.authority.pubkey(),
+            &realm.data.community_mint,
+            &self.payer.pubkey(),
+            voter.voter_weight_record_address,
+            realm.get_max_voter_weight_record_address(),
+            vote,
+        )
+    }
+
+    pub fn cast_vote_with(&self, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner, vote: Vote) -> ClientResult<Outcome> {
+        let cast_vote_instruction: Instruction = self.cast_vote_instruction(realm, governance, proposal, voter, vote);
         
-        let transaction: Transaction =
-            Transaction::new_signed_with_payer(
+        self.process_transaction(
                 &[
                     cast_vote_instruction,
                 ],
-                Some(&voter_authority_pubkey),
                 &[
-                    &voter.authority,
+                    voter.authority.as_ref(),
                 ],
-                self.solana_client.get_latest_blockhash().unwrap(),
+            )
+    }
+
+    /// Withdraws the vote while the proposal is voting, otherwise only marks the vote record relinquished
+    pub fn relinquish_vote(&self, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner) -> ClientResult<Outcome> {
+        let relinquish_vote_instruction: Instruction =
+            relinquish_vote(
+                &self.spl_governance_program_address,
+                &governance.address,
+                &proposal.address,
+                &voter.token_owner_record_address,
+                &realm.data.community_mint,
+                Some(voter.authority.pubkey()),
+                Some(self.payer.pubkey()),
             );
-        
-        self.solana_client.send_and_confirm_transaction(&transaction)
+
+        self.process_transaction(
+                &[
+                    relinquish_vote_instruction,
+                ],
+                &[
+                    voter.authority.as_ref(),
+                ],
+            )
+    }
+}
+
+/// `transaction-NNN.json` numbered after the highest file already in `directory`, so earlier exports are never overwritten
+fn get_export_path(directory: &Path) -> ClientResult<PathBuf> {
+    let highest_index: usize =
+        fs::read_dir(directory)?
+            .filter_map(|entry| entry.ok() )
+            .filter_map(|entry| {
+                entry.file_name().to_str()
+                    .and_then(|name| name.strip_prefix("transaction-") )
+                    .and_then(|name| name.strip_suffix(".json") )
+                    .and_then(|index| index.parse::<usize>().ok() )
+            })
+            .max()
+            .unwrap_or(0);
+    Ok(directory.join(format!("transaction-{:03}.json", highest_index + 1)))
+}
+
+/// `Vote` of `cast_vote`: approve the single option or deny
+pub fn yes_no_vote(vote_yes_no: bool) -> Vote {
+    if vote_yes_no {
+        Vote::Approve(vec![
+            VoteChoice {
+                rank: 0,
+                weight_percentage: 100,
+            }
+        ])
+    } else {
+        Vote::Deny
     }
 }
 
-#[derive(Debug)]
 pub struct Realm {
-    authority: Keypair,
+    authority: Arc<dyn Signer>,
     pub address: Pubkey,
     data: RealmV2,
     max_voter_weight_addin_address: Option<Pubkey>,
+    /// Set for addins whose max voter weight record is not the fixed-weights PDA (mock addin)
+    max_voter_weight_record_address: Option<Pubkey>,
     // voter_weight_addin_address: Option<Pubkey>,
 }
 
+impl fmt::Debug for Realm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Realm")
+            .field("authority", &self.authority.pubkey())
+            .field("address", &self.address)
+            .field("data", &self.data)
+            .field("max_voter_weight_addin_address", &self.max_voter_weight_addin_address)
+            .field("max_voter_weight_record_address", &self.max_voter_weight_record_address)
+            .finish()
+    }
+}
+
+impl Realm {
+    pub fn get_authority(&self) -> Arc<dyn Signer> {
+        self.authority.clone()
+    }
+    pub fn with_max_voter_weight_record(mut self, max_voter_weight_record_address: Pubkey) -> Self {
+        self.max_voter_weight_record_address = Some(max_voter_weight_record_address);
+        self
+    }
+    pub fn get_max_voter_weight_record_address(&self) -> Option<Pubkey> {
+        match (self.max_voter_weight_record_address, self.max_voter_weight_addin_address) {
+            (Some(max_voter_weight_record_address), _) => Some(max_voter_weight_record_address),
+            (None, Some(max_voter_weight_addin_address)) => {
+                let (max_voter_weight_record_address,_) = get_max_voter_weight_address(&max_voter_weight_addin_address, &self.address, &self.data.community_mint);
+                Some(max_voter_weight_record_address)
+            },
+            (None, None) => None,
+        }
+    }
+    pub fn get_community_mint(&self) -> Pubkey {
+        self.data.community_mint
+    }
+    pub fn get_name(&self) -> String {
+        self.data.name.clone()
+    }
+    pub fn get_data(&self) -> &RealmV2 {
+        &self.data
+    }
+}
+
 #[derive(Debug)]
 pub struct Governance {
-    address: Pubkey,
+    pub address: Pubkey,
     data: GovernanceV2,
 }
 
@@ -700,6 +1546,15 @@ impl Governance {
     pub fn get_proposal_count(&self) -> u32 {
         self.data.proposals_count
     }
+    pub fn get_config(&self) -> GovernanceConfig {
+        self.data.config.clone()
+    }
+    pub fn get_governed_account(&self) -> Pubkey {
+        self.data.governed_account
+    }
+    pub fn get_data(&self) -> &GovernanceV2 {
+        &self.data
+    }
 }
 
 #[derive(Debug)]
@@ -708,12 +1563,65 @@ pub struct Proposal {
     pub data: ProposalV2,
 }
 
-#[derive(Debug)]
+impl Proposal {
+    pub fn get_address(&self) -> Pubkey {
+        self.address
+    }
+    /// Option labels with the vote weight each option has received so far
+    pub fn get_option_vote_weights(&self) -> Vec<(String,u64)> {
+        self.data.options.iter()
+            .map(|option| (option.label.clone(), option.vote_weight) )
+            .collect()
+    }
+    pub fn get_deny_vote_weight(&self) -> Option<u64> {
+        self.data.deny_vote_weight
+    }
+}
+
 pub struct TokenOwner {
-    pub authority: Keypair,
+    /// Governing token owner or its governance delegate
+    pub authority: Arc<dyn Signer>,
     token_owner_record_address: Pubkey,
     token_owner_record: TokenOwnerRecordV2,
     // voter_weight_record_authority: Option<Keypair>,
     voter_weight_record_address: Option<Pubkey>,
     // voter_weight_record: Option<VoterWeightRecord>,
 }
+
+impl fmt::Debug for TokenOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenOwner")
+            .field("authority", &self.authority.pubkey())
+            .field("token_owner_record_address", &self.token_owner_record_address)
+            .field("token_owner_record", &self.token_owner_record)
+            .field("voter_weight_record_address", &self.voter_weight_record_address)
+            .finish()
+    }
+}
+
+impl TokenOwner {
+    pub fn get_owner_pubkey(&self) -> Pubkey {
+        self.token_owner_record.governing_token_owner
+    }
+    pub fn get_governance_delegate(&self) -> Option<Pubkey> {
+        self.token_owner_record.governance_delegate
+    }
+    pub fn get_token_owner_record_address(&self) -> Pubkey {
+        self.token_owner_record_address
+    }
+    pub fn get_token_owner_record(&self) -> &TokenOwnerRecordV2 {
+        &self.token_owner_record
+    }
+    pub fn get_voter_weight_record_address(&self) -> Option<Pubkey> {
+        self.voter_weight_record_address
+    }
+    /// Same record with another signer, which the program accepts only if it is the owner or the delegate
+    pub fn with_authority(&self, authority: Arc<dyn Signer>) -> TokenOwner {
+        TokenOwner {
+            authority,
+            token_owner_record_address: self.token_owner_record_address,
+            token_owner_record: self.token_owner_record.clone(),
+            voter_weight_record_address: self.voter_weight_record_address,
+        }
+    }
+}
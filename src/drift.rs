@@ -0,0 +1,67 @@
+use std::fmt;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use crate::manifest::AccountKind;
+
+/// Field of an existing account that differs from the parameters it was requested with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    pub kind: AccountKind,
+    pub address: Pubkey,
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}: expected {}, found {}", self.kind, self.address, self.field, self.expected, self.actual)
+    }
+}
+
+/// What the interactor does when an existing account drifted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DriftPolicy {
+    /// The operation fails, the drifts are kept for `take_drifts`
+    Error,
+    /// The existing account is used, the drifts are kept for `take_drifts`
+    Warn,
+    Ignore,
+}
+
+/// Drifts of one account, collected field by field
+pub struct DriftCheck {
+    kind: AccountKind,
+    address: Pubkey,
+    drifts: Vec<Drift>,
+}
+
+impl DriftCheck {
+    pub fn new(kind: AccountKind, address: &Pubkey) -> Self {
+        DriftCheck {
+            kind,
+            address: *address,
+            drifts: Vec::new(),
+        }
+    }
+
+    pub fn compare<T: fmt::Debug + PartialEq>(mut self, field: &'static str, expected: &T, actual: &T) -> Self {
+        if expected != actual {
+            self.drifts.push(
+                Drift {
+                    kind: self.kind,
+                    address: self.address,
+                    field,
+                    expected: format!("{:?}", expected),
+                    actual: format!("{:?}", actual),
+                }
+            );
+        }
+        self
+    }
+
+    pub fn into_drifts(self) -> Vec<Drift> {
+        self.drifts
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use spl_governance::state::{
+    governance::{
+        GovernanceV2,
+        get_governance_address,
+        get_mint_governance_address,
+        get_program_governance_address,
+        get_token_governance_address,
+    },
+    proposal::{ ProposalV2, get_proposal_address },
+    proposal_transaction::get_proposal_transaction_address,
+    realm::{ RealmV2, get_governing_token_holding_address, get_realm_address },
+    realm_config::{ RealmConfigAccount, get_realm_config_address },
+    signatory_record::get_signatory_record_address,
+    token_owner_record::get_token_owner_record_address,
+    vote_record::get_vote_record_address,
+};
+
+use spl_governance_addin_fixed_weights::instruction::{
+    get_max_voter_weight_address,
+    get_voter_weight_address,
+};
+
+use crate::commands::SplGovernanceInteractor;
+use crate::output::{ Describe, Fields };
+
+/// Program derived address of a realm and whether an account exists there
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivedAddress {
+    /// e.g. `token-owner-record <owner> <mint>` or `vote-record <proposal> <owner>`
+    pub name: String,
+    pub address: Pubkey,
+    pub exists: bool,
+}
+
+impl fmt::Display for DerivedAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", if self.exists { "+" } else { "-" }, self.name, self.address)
+    }
+}
+
+impl Describe for DerivedAddress {
+    fn describe(&self, fields: Fields) -> Fields {
+        fields
+            .text("name", &self.name)
+            .pubkey("address", &self.address)
+            .flag("exists", self.exists)
+    }
+}
+
+struct Explorer<'a> {
+    interactor: &'a SplGovernanceInteractor,
+    program_id: Pubkey,
+    addresses: Vec<DerivedAddress>,
+}
+
+impl<'a> Explorer<'a> {
+    fn add(&mut self, name: String, address: Pubkey) -> bool {
+        let exists: bool = self.interactor.account_exists(&address);
+        self.addresses.push(DerivedAddress { name, address, exists });
+        exists
+    }
+
+    fn add_proposal(&mut self, governance_pubkey: &Pubkey, governing_token_mint: &Pubkey, index: u32, owners: &[Pubkey], realm_pubkey: &Pubkey) -> Result<(),String> {
+        let proposal_pubkey: Pubkey = get_proposal_address(&self.program_id, governance_pubkey, governing_token_mint, &index.to_le_bytes());
+        if !self.add(format!("proposal {} {} {}", governance_pubkey, governing_token_mint, index), proposal_pubkey) {
+            return Ok(());
+        }
+        let proposal: ProposalV2 = self.interactor.get_proposal_data(&proposal_pubkey).map_err(|err| format!("proposal {}: {}", proposal_pubkey, err) )?;
+
+        for (option_index, option) in proposal.options.iter().enumerate() {
+            for transaction_index in 0..option.transactions_next_index {
+                let proposal_transaction_pubkey: Pubkey = get_proposal_transaction_address(&self.program_id, &proposal_pubkey, &(option_index as u8).to_le_bytes(), &transaction_index.to_le_bytes());
+                self.add(format!("proposal-transaction {} {} {}", proposal_pubkey, option_index, transaction_index), proposal_transaction_pubkey);
+            }
+        }
+        for owner in owners.iter() {
+            let token_owner_record_pubkey: Pubkey = get_token_owner_record_address(&self.program_id, realm_pubkey, governing_token_mint, owner);
+            self.add(format!("signatory-record {} {}", proposal_pubkey, owner), get_signatory_record_address(&self.program_id, &proposal_pubkey, owner));
+            self.add(format!("vote-record {} {}", proposal_pubkey, owner), get_vote_record_address(&self.program_id, &proposal_pubkey, &token_owner_record_pubkey));
+        }
+        Ok(())
+    }
+}
+
+/// Every PDA of the realm `realm_name`: realm, holding accounts, config, token owner records and fixed weights addin
+/// records of `owners`, governances of `governed_accounts` (all four kinds) with their proposals, transactions,
+/// signatory and vote records. The community mint is read from the realm and only needed if it does not exist
+pub fn explore_realm(interactor: &SplGovernanceInteractor, realm_name: &str, community_mint: Option<Pubkey>, owners: &[Pubkey], governed_accounts: &[Pubkey]) -> Result<Vec<DerivedAddress>,String> {
+    let program_id: Pubkey = interactor.get_program_address();
+    let mut explorer: Explorer = Explorer { interactor, program_id, addresses: Vec::new() };
+
+    let realm_pubkey: Pubkey = get_realm_address(&program_id, realm_name);
+    explorer.add(format!("realm {}", realm_name), realm_pubkey);
+
+    let realm: Option<RealmV2> = interactor.get_versioned_account::<RealmV2>(&realm_pubkey).ok().map(|realm| realm.data );
+    let community_mint: Pubkey = realm.as_ref().map(|realm| realm.community_mint ).or(community_mint)
+        .ok_or_else(|| format!("Realm {} does not exist, the community mint is needed", realm_name) )?;
+    let mut mints: Vec<Pubkey> = vec![ community_mint ];
+    mints.extend(realm.as_ref().and_then(|realm| realm.config.council_mint ));
+
+    explorer.add("realm-config".to_string(), get_realm_config_address(&program_id, &realm_pubkey));
+    let voter_weight_addin: Pubkey =
+        interactor.get_realm_config(&realm_pubkey)
+            .and_then(|realm_config: RealmConfigAccount| realm_config.community_voter_weight_addin )
+            .unwrap_or_else(|| interactor.get_voter_weight_addin_address() );
+
+    for mint in mints.iter() {
+        explorer.add(format!("holding-account {}", mint), get_governing_token_holding_address(&program_id, &realm_pubkey, mint));
+        for owner in owners.iter() {
+            explorer.add(format!("token-owner-record {} {}", owner, mint), get_token_owner_record_address(&program_id, &realm_pubkey, mint, owner));
+        }
+    }
+
+    for owner in owners.iter() {
+        let (voter_weight_record_pubkey, _): (Pubkey, u8) = get_voter_weight_address(&voter_weight_addin, &realm_pubkey, &community_mint, owner);
+        explorer.add(format!("voter-weight-record {}", owner), voter_weight_record_pubkey);
+    }
+    let (max_voter_weight_record_pubkey, _): (Pubkey, u8) = get_max_voter_weight_address(&voter_weight_addin, &realm_pubkey, &community_mint);
+    explorer.add("max-voter-weight-record".to_string(), max_voter_weight_record_pubkey);
+
+    for governed_account in governed_accounts.iter() {
+        let governances: [(&str, Pubkey); 4] = [
+            ("account", get_governance_address(&program_id, &realm_pubkey, governed_account)),
+            ("program", get_program_governance_address(&program_id, &realm_pubkey, governed_account)),
+            ("mint", get_mint_governance_address(&program_id, &realm_pubkey, governed_account)),
+            ("token", get_token_governance_address(&program_id, &realm_pubkey, governed_account)),
+        ];
+        for (kind, governance_pubkey) in governances.iter() {
+            if !explorer.add(format!("{}-governance {}", kind, governed_account), *governance_pubkey) {
+                continue;
+            }
+            let governance: GovernanceV2 = interactor.get_versioned_account::<GovernanceV2>(governance_pubkey).map_err(|err| err.to_string() )?.data;
+            // Proposal indexes are shared by the community and council proposals
+            for index in 0..governance.proposals_count {
+                for mint in mints.iter() {
+                    explorer.add_proposal(governance_pubkey, mint, index, owners, &realm_pubkey)?;
+                }
+            }
+        }
+    }
+
+    Ok(explorer.addresses)
+}
@@ -0,0 +1,19 @@
+// pub mod tokens;
+pub mod commands;
+pub mod signers;
+pub mod batch;
+pub mod simulation;
+pub mod offline;
+pub mod send;
+pub mod backend;
+pub mod program_test;
+pub mod negative;
+pub mod tally;
+pub mod scenario;
+pub mod manifest;
+pub mod drift;
+pub mod reconcile;
+pub mod versioned;
+pub mod output;
+pub mod address_book;
+pub mod explorer;
@@ -1,6 +1,17 @@
 
+use std::error::Error;
+use std::fmt;
+use std::path::{ Path, PathBuf };
+use std::sync::Arc;
+
+use clap::{ Args, Parser, Subcommand };
+
+use serde_json::{ Map, Value };
+
 use solana_sdk::{
+    commitment_config::CommitmentConfig,
     pubkey::{ Pubkey },
+    signature::{ Signature },
     signer::{
         Signer,
         keypair::{ Keypair, read_keypair_file },
@@ -17,21 +28,34 @@ use spl_governance::{
         governance::{
             GovernanceConfig,
         },
+        realm::{
+            RealmV2,
+        },
+        proposal::{
+            ProposalV2,
+            // VoteType,
+        },
+        vote_record::{
+            VoteRecordV2,
+            // VoteChoice,
+        },
     },
 };
 
-use spl_governance_addin_fixed_weights::{
-    instruction::{
-        get_max_voter_weight_address,
-        get_voter_weight_address,
-    }
-};
-
-// mod tokens;
-mod commands;
+use governance_test_scripts::{ backend, commands, signers, offline, send, negative, tally, scenario, manifest, drift, reconcile, output, address_book, explorer };
 
-use commands::{ Realm, Governance, Proposal, TokenOwner };
+use commands::{ Realm, Governance, Proposal, TokenOwner, ExecutionMode };
+use offline::{ DurableNonce, OfflineTransaction };
+use send::SendStrategy;
+use backend::RpcBackend;
+use manifest::{ AccountKind, Manifest };
+use drift::DriftPolicy;
+use output::{ Output, OutputFormat };
+use address_book::AddressBook;
+use tally::{ VoteTally, cast_vote_and_compare };
+use commands::yes_no_vote;
 
+const PAYER_KEY_FILE_PATH: &'static str = "../neon-spl-governance/artifacts/payer.keypair";
 const GOVERNANCE_KEY_FILE_PATH: &'static str = "/media/mich/speedwork/SolanaProgs/solana-program-library/target/deploy/spl_governance-keypair.json";
 const VOTER_WEIGHT_ADDIN_KEY_FILE_PATH: &'static str = "../neon-spl-governance/artifacts/addin-fixed-weights.keypair";
 const COMMUTINY_MINT_KEY_FILE_PATH: &'static str = "../artifacts/dev/token_mints/USDT.keypair";
@@ -47,53 +71,551 @@ const VOTER5_KEY_FILE_PATH: &'static str = "../neon-spl-governance/artifacts/vot
 // const VOTER2_WEIGHT_RECORD_KEY_FILE_PATH: &'static str = "/media/mich/speedwork/NeonLabs/artifacts/vw-addin/voter2-voter-weight-record.keypair";
 // const VOTER3_WEIGHT_RECORD_KEY_FILE_PATH: &'static str = "/media/mich/speedwork/NeonLabs/artifacts/vw-addin/voter3-voter-weight-record.keypair";
 
+/// Keypair files of `run` by their address book names
+const KEY_FILES: [(&'static str, &'static str); 10] = [
+    ("payer", PAYER_KEY_FILE_PATH),
+    ("spl-governance", GOVERNANCE_KEY_FILE_PATH),
+    ("addin-fixed-weights", VOTER_WEIGHT_ADDIN_KEY_FILE_PATH),
+    ("USDT", COMMUTINY_MINT_KEY_FILE_PATH),
+    ("wBAL", GOVERNED_MINT_KEY_FILE_PATH),
+    ("voter1", VOTER1_KEY_FILE_PATH),
+    ("voter2", VOTER2_KEY_FILE_PATH),
+    ("voter3", VOTER3_KEY_FILE_PATH),
+    ("voter4", VOTER4_KEY_FILE_PATH),
+    ("voter5", VOTER5_KEY_FILE_PATH),
+];
+
 // const REALM_NAME: &'static str = "Test Realm";
 const REALM_NAME: &'static str = "_Test_Realm_5";
 // const REALM_NAME: &'static str = "Test Realm 6";
 const PROPOSAL_NAME: &'static str = "Proposal To Vote";
 const PROPOSAL_DESCRIPTION: &'static str = "proposal_description";
 
+#[derive(Parser)]
+#[clap(about = "SPL Governance test scripts")]
+struct Cli {
+    #[clap(long, default_value = "http://localhost:8899")]
+    url: String,
+    /// Manifest of the created accounts, continued by every run
+    #[clap(long, default_value = "manifest.json")]
+    manifest: PathBuf,
+    /// table or json, for accounts printed by `run` and `list`
+    #[clap(long, global = true, default_value = "table")]
+    output: OutputFormat,
+    /// Names of pubkeys (`name = "<pubkey>"` lines) shown next to them and accepted as arguments,
+    /// together with the manifest names and the keypair files of `run`
+    #[clap(long, global = true, default_value = "address-book.toml")]
+    address_book: PathBuf,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the test realm, voters, governance and proposal and cast votes (default)
+    Run(RunArgs),
+    /// Add signatures to exported transaction files
+    Sign {
+        files: Vec<PathBuf>,
+        /// Keypair file or signer description (see `signers::signer_from_path`)
+        #[clap(long)]
+        signer: String,
+    },
+    /// Send exported transaction files once all signatures are collected
+    Submit {
+        files: Vec<PathBuf>,
+    },
+    /// Create a durable nonce account (or check an existing one) for `run --nonce-account`
+    CreateNonce {
+        /// Keypair of the new nonce account
+        #[clap(long)]
+        nonce_account: String,
+        #[clap(long)]
+        nonce_authority: String,
+    },
+    /// Run operations the governance program must reject (all, or the named one) on an in-process bank
+    Negative {
+        name: Option<String>,
+    },
+    /// Run scenario files (see `scenario::Scenario`) on an in-process bank and print a pass/fail report
+    Scenario {
+        files: Vec<PathBuf>,
+    },
+    /// Compare the realm config, governances and voters with a desired state file (see `reconcile::DesiredState`)
+    Plan {
+        file: PathBuf,
+    },
+    /// Reconcile the desired state file: realm config directly, governance configs through proposals
+    Apply {
+        file: PathBuf,
+        /// Keypair of the realm authority, which also creates the proposals: a signer path or a keypair name of `run`, e.g. voter1
+        #[clap(long)]
+        realm_authority: String,
+    },
+    /// List governance program accounts found with getProgramAccounts
+    #[clap(subcommand)]
+    List(ListCommand),
+    /// Print every PDA of a realm and whether it exists; accounts are given as pubkeys or address book names
+    Explore {
+        #[clap(default_value = REALM_NAME)]
+        realm_name: String,
+        /// Needed only if the realm does not exist
+        #[clap(long)]
+        community_mint: Option<String>,
+        /// Token owners whose records are derived
+        #[clap(long)]
+        owner: Vec<String>,
+        /// Governed accounts whose governances and proposals are derived
+        #[clap(long)]
+        governed: Vec<String>,
+    },
+    /// Print the address recorded in the manifest under `name` (e.g. "governance wBAL"), or all of them
+    Manifest {
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+/// Accounts are given as pubkeys or address book names
+enum ListCommand {
+    Realms,
+    Governances {
+        realm: String,
+    },
+    Proposals {
+        governance: String,
+    },
+    TokenOwnerRecords {
+        realm: String,
+    },
+    /// Votes of a proposal, checked against its vote weights
+    VoteRecords {
+        proposal: String,
+    },
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Simulate transactions instead of sending them
+    #[clap(long)]
+    dry_run: bool,
+    /// Write unsigned transactions to this directory instead of sending them
+    #[clap(long)]
+    export: Option<PathBuf>,
+    /// Signer of the realm authority (also the first voter), e.g. `pubkey:PUBKEY` when its key is offline
+    #[clap(long)]
+    realm_authority: Option<String>,
+    /// Build transactions on this durable nonce account instead of a recent blockhash
+    #[clap(long, requires = "nonce_authority")]
+    nonce_account: Option<Pubkey>,
+    #[clap(long)]
+    nonce_authority: Option<String>,
+    /// Attempts after a failed send (expired blockhash, transport errors)
+    #[clap(long, default_value_t = 3)]
+    retries: u32,
+    #[clap(long)]
+    skip_preflight: bool,
+    /// processed, confirmed or finalized
+    #[clap(long, default_value = "confirmed")]
+    commitment: CommitmentConfig,
+    /// Stop when an existing realm, token owner record or governance differs from the requested parameters
+    #[clap(long)]
+    fail_on_drift: bool,
+}
+
+impl Default for RunArgs {
+    fn default() -> Self {
+        RunArgs {
+            dry_run: false,
+            export: None,
+            realm_authority: None,
+            nonce_account: None,
+            nonce_authority: None,
+            retries: 3,
+            skip_preflight: false,
+            commitment: CommitmentConfig::confirmed(),
+            fail_on_drift: false,
+        }
+    }
+}
+
 fn main() {
+    let cli: Cli = Cli::parse();
+    let output: Output = Output::new(cli.output);
+
+    match cli.command {
+        None => {
+            let address_book: Arc<AddressBook> = get_address_book(&cli.address_book, &cli.manifest);
+            if run(&cli.url, &cli.manifest, output.with_address_book(address_book), RunArgs::default()).is_none() {
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Run(args)) => {
+            let address_book: Arc<AddressBook> = get_address_book(&cli.address_book, &cli.manifest);
+            if run(&cli.url, &cli.manifest, output.with_address_book(address_book), args).is_none() {
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Sign { files, signer }) => {
+            let signer: Arc<dyn Signer> =
+                signers::signer_from_path(&signer)
+                    .unwrap_or_else(|err| {
+                        println!("Signer {} not loaded: {}", signer, err);
+                        std::process::exit(1);
+                    });
+            let mut failed: usize = 0;
+            for file in files.iter() {
+                match sign_file(file, signer.as_ref()) {
+                    Ok((signature, missing_signers)) => println!("{}: signed by {} ({}), missing signers: {:?}", file.display(), signer.pubkey(), signature, missing_signers),
+                    Err(err) => {
+                        failed += 1;
+                        println!("{}: not signed: {}", file.display(), err);
+                    },
+                }
+            }
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Submit { files }) => {
+            let backend: RpcBackend = RpcBackend::new(&cli.url);
+            let send_strategy: SendStrategy = SendStrategy::default();
+            let mut failed: usize = 0;
+            for file in files.iter() {
+                match OfflineTransaction::load(file).and_then(|offline_transaction| offline::submit(&backend, &send_strategy, &offline_transaction) ) {
+                    Ok(signature) => println!("{}: {}", file.display(), signature),
+                    Err(err) => {
+                        failed += 1;
+                        println!("{}: not submitted: {}", file.display(), err);
+                    },
+                }
+            }
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        },
+        Some(Command::CreateNonce { nonce_account, nonce_authority }) => {
+            let payer: Arc<dyn Signer> = signers::signer_from_path(PAYER_KEY_FILE_PATH).unwrap();
+            let program_id: Pubkey = read_keypair_file(GOVERNANCE_KEY_FILE_PATH).unwrap().pubkey();
+            let voter_weight_addin_pubkey: Pubkey = read_keypair_file(VOTER_WEIGHT_ADDIN_KEY_FILE_PATH).unwrap().pubkey();
+            let interactor = commands::SplGovernanceInteractor::new(&cli.url, program_id, voter_weight_addin_pubkey, payer);
+
+            let nonce_account: Arc<dyn Signer> = signers::signer_from_path(&nonce_account).unwrap();
+            let nonce_authority: Arc<dyn Signer> = signers::signer_from_path(&nonce_authority).unwrap();
+            let durable_nonce: DurableNonce = interactor.setup_durable_nonce(nonce_account, nonce_authority).unwrap();
+            println!("Durable Nonce Account: {}, Authority: {}", durable_nonce.account, durable_nonce.authority.pubkey());
+        },
+        Some(Command::Negative { name }) => {
+            let scenarios: Vec<negative::NegativeScenario> =
+                negative::catalog().into_iter()
+                    .filter(|scenario| name.as_deref().map_or(true, |name| scenario.name == name) )
+                    .collect();
+            if scenarios.is_empty() {
+                println!("Unknown scenario {:?}, available: {:?}", name, negative::catalog().iter().map(|scenario| scenario.name ).collect::<Vec<_>>());
+            }
+            for scenario in scenarios.iter() {
+                match scenario.run() {
+                    Ok(()) => println!("PASS {}: {} ({:?})", scenario.name, scenario.description, scenario.expected_error),
+                    Err(err) => println!("FAIL {}: {} {}", scenario.name, scenario.description, err),
+                }
+            }
+        },
+        Some(Command::Scenario { files }) => {
+            let mut failed: usize = 0;
+            for file in files.iter() {
+                match scenario::Scenario::load(file) {
+                    Ok(scenario) => {
+                        let report: scenario::ScenarioReport = scenario.run();
+                        if !report.is_passed() {
+                            failed += 1;
+                        }
+                        print!("{}", report);
+                    },
+                    Err(err) => {
+                        failed += 1;
+                        println!("FAIL {}", err);
+                    },
+                }
+            }
+            if failed > 0 {
+                println!("{} of {} scenarios failed", failed, files.len());
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Plan { file }) => {
+            let address_book: Arc<AddressBook> = get_address_book(&cli.address_book, &cli.manifest);
+            let desired: reconcile::DesiredState = reconcile::DesiredState::load(&file).unwrap();
+            // Nothing is signed while planning
+            let (interactor, realm) = load_realm(&cli.url, &desired.realm_name, None);
+            let changes: Vec<reconcile::Change> = reconcile::plan(&interactor, &realm, &desired, &address_book).unwrap();
+            if changes.is_empty() {
+                println!("No changes");
+            }
+            for change in changes.iter() {
+                println!("{}", address_book.annotate_text(&change.to_string()));
+            }
+        },
+        Some(Command::Apply { file, realm_authority }) => {
+            let address_book: Arc<AddressBook> = get_address_book(&cli.address_book, &cli.manifest);
+            let desired: reconcile::DesiredState = reconcile::DesiredState::load(&file).unwrap();
+            let (interactor, realm) = load_realm(&cli.url, &desired.realm_name, Some(get_key_file_path(&realm_authority)));
+            let changes: Vec<reconcile::Change> = reconcile::plan(&interactor, &realm, &desired, &address_book).unwrap();
+            if changes.is_empty() {
+                println!("No changes");
+                return;
+            }
+            // Proposals are created by the realm authority, which is also the first voter
+            let set_up_proposer = || {
+                interactor.create_token_owner_record(&realm, realm.get_authority())
+                    .and_then(|proposer| interactor.setup_voter_weight_record_fixed(&realm, proposer) )
+            };
+            for (change, result) in changes.iter().zip(reconcile::apply(&interactor, &realm, set_up_proposer, &changes)) {
+                println!("{}", address_book.annotate_text(&format!("{}\n  => {:?}", change, result)));
+            }
+        },
+        Some(Command::List(list_command)) => {
+            let address_book: Arc<AddressBook> = get_address_book(&cli.address_book, &cli.manifest);
+            let output: Output = output.with_address_book(address_book.clone());
+            let interactor = load_interactor(&cli.url);
+            match list_command {
+                ListCommand::Realms => {
+                    // Realms of different community mints, amounts are not scaled
+                    output.print_list(&interactor.get_realms().unwrap());
+                },
+                ListCommand::Governances { realm } => {
+                    let realm: Pubkey = address_book.resolve(&realm).unwrap();
+                    let output: Output = get_realm_output(&interactor, output, &realm);
+                    output.print_list(&interactor.get_governances(&realm).unwrap());
+                },
+                ListCommand::Proposals { governance } => {
+                    let governance: Pubkey = address_book.resolve(&governance).unwrap();
+                    let proposals: Vec<(Pubkey, ProposalV2)> = interactor.get_proposals(&governance).unwrap();
+                    let output: Output = get_output(&interactor, output, proposals.first().map(|(_, proposal)| proposal.governing_token_mint ));
+                    output.print_list(&proposals);
+                },
+                ListCommand::TokenOwnerRecords { realm } => {
+                    let realm: Pubkey = address_book.resolve(&realm).unwrap();
+                    let output: Output = get_realm_output(&interactor, output, &realm);
+                    output.print_list(&interactor.get_token_owner_records(&realm).unwrap());
+                },
+                ListCommand::VoteRecords { proposal } => {
+                    let proposal: Pubkey = address_book.resolve(&proposal).unwrap();
+                    let proposal_data: ProposalV2 =
+                        match interactor.get_proposal_data(&proposal) {
+                            Ok(proposal_data) => proposal_data,
+                            Err(err) => {
+                                eprintln!("Proposal {}: {}", proposal, err);
+                                std::process::exit(1);
+                            },
+                        };
+                    let output: Output = get_output(&interactor, output, Some(proposal_data.governing_token_mint));
+                    let vote_records: Vec<(Pubkey, VoteRecordV2)> = interactor.get_vote_records(&proposal).unwrap();
+                    let vote_record_data: Vec<VoteRecordV2> = vote_records.iter().map(|(_, vote_record)| vote_record.clone() ).collect();
+                    let mismatches: Vec<String> = tally::get_vote_record_mismatches(&proposal_data, &vote_record_data);
+
+                    match output.get_format() {
+                        OutputFormat::Json => {
+                            let mut report: Map<String, Value> = Map::new();
+                            report.insert("vote_records".to_string(), output.get_list_json(&vote_records));
+                            report.insert("mismatches".to_string(), Value::from(mismatches));
+                            println!("{}", serde_json::to_string_pretty(&Value::Object(report)).unwrap());
+                        },
+                        OutputFormat::Table => {
+                            output.print_list(&vote_records);
+                            if mismatches.is_empty() {
+                                println!("Vote records match the proposal vote weights");
+                            }
+                            for mismatch in mismatches.iter() {
+                                println!("Mismatch: {}", mismatch);
+                            }
+                        },
+                    }
+                },
+            }
+        },
+        Some(Command::Explore { realm_name, community_mint, owner, governed }) => {
+            let address_book: Arc<AddressBook> = get_address_book(&cli.address_book, &cli.manifest);
+            let output: Output = output.with_address_book(address_book.clone());
+            let interactor = load_interactor(&cli.url);
+            let community_mint: Option<Pubkey> = community_mint.map(|mint| address_book.resolve(&mint).unwrap() );
+            let owners: Vec<Pubkey> = owner.iter().map(|owner| address_book.resolve(owner).unwrap() ).collect();
+            let governed_accounts: Vec<Pubkey> = governed.iter().map(|governed| address_book.resolve(governed).unwrap() ).collect();
+            let addresses: Vec<explorer::DerivedAddress> = explorer::explore_realm(&interactor, &realm_name, community_mint, &owners, &governed_accounts).unwrap();
+            output.print_list(&addresses);
+        },
+        Some(Command::Manifest { name }) => {
+            let manifest: Manifest =
+                match Manifest::load(&cli.manifest) {
+                    Ok(manifest) => manifest,
+                    Err(err) => {
+                        eprintln!("Manifest {}: {}", cli.manifest.display(), err);
+                        std::process::exit(1);
+                    },
+                };
+            match name {
+                Some(name) => {
+                    match manifest.get_address(&name) {
+                        Some(address) => println!("{}", address),
+                        None => {
+                            eprintln!("{} is not in the manifest {}", name, cli.manifest.display());
+                            std::process::exit(1);
+                        },
+                    }
+                },
+                None => {
+                    for (name, entry) in manifest.accounts.iter() {
+                        println!("{}: {}", name, entry.address);
+                    }
+                },
+            }
+        },
+    }
+}
+
+/// Interactor on the program and addin of `run`
+fn load_interactor(url: &str) -> commands::SplGovernanceInteractor {
+    let payer: Arc<dyn Signer> = signers::signer_from_path(PAYER_KEY_FILE_PATH).unwrap();
+    let program_id: Pubkey = read_keypair_file(GOVERNANCE_KEY_FILE_PATH).unwrap().pubkey();
+    let voter_weight_addin_pubkey: Pubkey = read_keypair_file(VOTER_WEIGHT_ADDIN_KEY_FILE_PATH).unwrap().pubkey();
+    commands::SplGovernanceInteractor::new(url, program_id, voter_weight_addin_pubkey, payer)
+}
 
-    let owner_keypair: Keypair = read_keypair_file(VOTER1_KEY_FILE_PATH).unwrap();
-    // let owner_pubkey: Pubkey = owner_keypair.pubkey();
-    // println!("Owner Pubkey: {}", owner_pubkey);
+/// Interactor and existing realm named `realm_name`, with the addin of its realm config.
+/// Without `realm_authority` the payer stands in for it, for commands which do not sign as the authority
+fn load_realm(url: &str, realm_name: &str, realm_authority: Option<&str>) -> (commands::SplGovernanceInteractor, Realm) {
+    let interactor = load_interactor(url);
+    let realm_address: Pubkey = interactor.get_realm_address(realm_name);
+    let voter_weight_addin_pubkey: Option<Pubkey> =
+        interactor.get_realm_config(&realm_address).and_then(|realm_config| realm_config.community_voter_weight_addin );
+
+    let realm_authority: Arc<dyn Signer> = signers::signer_from_path(realm_authority.unwrap_or(PAYER_KEY_FILE_PATH)).unwrap();
+    let realm: Realm = interactor.get_realm(realm_authority, realm_name, voter_weight_addin_pubkey)
+        .unwrap_or_else(|err| panic!("Realm {} ({}) not loaded: {}", realm_name, realm_address, err) );
+    (interactor, realm)
+}
+
+/// Address book file, then the manifest labels and account names, then the keypair files of `run` which exist
+fn load_address_book(address_book_path: &Path, manifest_path: &Path) -> Result<AddressBook,String> {
+    let mut address_book: AddressBook = AddressBook::load_or_default(address_book_path)
+        .map_err(|err| format!("Address book {}: {}", address_book_path.display(), err) )?;
+    let manifest: Manifest = Manifest::load_or_default(manifest_path)
+        .map_err(|err| format!("Manifest {}: {}", manifest_path.display(), err) )?;
+    address_book.add_manifest(&manifest);
+
+    for (name, path) in KEY_FILES.iter() {
+        if let Ok(keypair) = read_keypair_file(path) {
+            address_book.insert(name, &keypair.pubkey());
+        }
+    }
+    Ok(address_book)
+}
+
+/// Address book of the commands taking or printing account names; exits if it cannot be loaded
+fn get_address_book(address_book_path: &Path, manifest_path: &Path) -> Arc<AddressBook> {
+    match load_address_book(address_book_path, manifest_path) {
+        Ok(address_book) => Arc::new(address_book),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        },
+    }
+}
+
+/// Keypair file of `run` named `name_or_path`, e.g. `voter1`, otherwise a signer path as is
+fn get_key_file_path(name_or_path: &str) -> &str {
+    KEY_FILES.iter()
+        .find(|(name, _)| *name == name_or_path )
+        .map(|(_, path)| *path )
+        .unwrap_or(name_or_path)
+}
+
+/// Amounts in UI units of `mint`, raw if the mint cannot be read
+fn get_output(interactor: &commands::SplGovernanceInteractor, output: Output, mint: Option<Pubkey>) -> Output {
+    match mint.and_then(|mint| interactor.get_mint_decimals(&mint).ok() ) {
+        Some(decimals) => output.with_decimals(decimals),
+        None => output,
+    }
+}
+
+fn get_realm_output(interactor: &commands::SplGovernanceInteractor, output: Output, realm_pubkey: &Pubkey) -> Output {
+    let community_mint: Option<Pubkey> =
+        interactor.get_versioned_account::<RealmV2>(realm_pubkey).ok()
+            .map(|realm| realm.data.community_mint );
+    get_output(interactor, output, community_mint)
+}
+
+/// Value of an operation of `run`. A failed operation, e.g. one whose transaction was not sent in dry-run
+/// and export modes, ends the run (`None`) after printing the outcomes: the following operations need its accounts
+fn check_sent<T, E: fmt::Debug>(interactor: &commands::SplGovernanceInteractor, output: &Output, result: Result<T,E>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            output.note(&format!("Run stopped: {:?}", err));
+            for outcome in interactor.take_outcomes() {
+                output.note(&outcome.to_string());
+            }
+            print_drifts(interactor, output);
+            None
+        },
+    }
+}
+
+/// Signs the exported transaction file in place, returns the signature and the signers still missing
+fn sign_file(file: &Path, signer: &dyn Signer) -> Result<(Signature, Vec<String>),Box<dyn Error>> {
+    let mut offline_transaction: OfflineTransaction = OfflineTransaction::load(file)?;
+    let signature: Signature = offline_transaction.sign(signer)?;
+    offline_transaction.save(file)?;
+    let missing_signers: Vec<String> = offline_transaction.missing_signers().iter().map(|signer| signer.to_string() ).collect();
+    Ok((signature, missing_signers))
+}
+
+/// Existing accounts which differ from what `run` requested and were used anyway (`DriftPolicy::Warn`)
+fn print_drifts(interactor: &commands::SplGovernanceInteractor, output: &Output) {
+    for drift in interactor.take_drifts() {
+        output.note(&format!("Drift: {}", drift));
+    }
+}
+
+fn run(url: &str, manifest_path: &Path, output: Output, args: RunArgs) -> Option<()> {
+
+    let payer: Arc<dyn Signer> = signers::signer_from_path(PAYER_KEY_FILE_PATH).unwrap();
+    output.note(&format!("Payer Pubkey: {}", payer.pubkey()));
 
     let program_keypair: Keypair = read_keypair_file(GOVERNANCE_KEY_FILE_PATH).unwrap();
     let program_id: Pubkey = program_keypair.pubkey();
-    println!("Governance Program Id: {}", program_id);
+    output.note(&format!("Governance Program Id: {}", program_id));
 
     let community_keypair: Keypair = read_keypair_file(COMMUTINY_MINT_KEY_FILE_PATH).unwrap();
     let community_pubkey: Pubkey = community_keypair.pubkey();
-    println!("Community Token Mint Pubkey: {}", community_pubkey);
+    output.note(&format!("Community Token Mint Pubkey: {}", community_pubkey));
 
     let voter_weight_addin_keypair: Keypair = read_keypair_file(VOTER_WEIGHT_ADDIN_KEY_FILE_PATH).unwrap();
     let voter_weight_addin_pubkey: Pubkey = voter_weight_addin_keypair.pubkey();
-    println!("Voter Weight Addin Pubkey: {}", voter_weight_addin_pubkey);
+    output.note(&format!("Voter Weight Addin Pubkey: {}", voter_weight_addin_pubkey));
 
     let governed_account_keypair: Keypair = read_keypair_file(GOVERNED_MINT_KEY_FILE_PATH).unwrap();
     let governed_account_pubkey: Pubkey = governed_account_keypair.pubkey();
-    println!("Governed Account (Mint) Pubkey: {}", governed_account_pubkey);
+    output.note(&format!("Governed Account (Mint) Pubkey: {}", governed_account_pubkey));
 
-    let voter1_keypair: Keypair = read_keypair_file(VOTER1_KEY_FILE_PATH).unwrap();
-    let voter1_pubkey: Pubkey = voter1_keypair.pubkey();
-    println!("Voter1 Pubkey: {}", voter1_pubkey);
+    let voter1: Arc<dyn Signer> = signers::signer_from_path(args.realm_authority.as_deref().unwrap_or(VOTER1_KEY_FILE_PATH)).unwrap();
+    let voter1_pubkey: Pubkey = voter1.pubkey();
+    output.note(&format!("Voter1 Pubkey: {}", voter1_pubkey));
 
-    let voter2_keypair: Keypair = read_keypair_file(VOTER2_KEY_FILE_PATH).unwrap();
-    let voter2_pubkey: Pubkey = voter2_keypair.pubkey();
-    println!("Voter2 Pubkey: {}", voter2_pubkey);
+    let voter2: Arc<dyn Signer> = signers::signer_from_path(VOTER2_KEY_FILE_PATH).unwrap();
+    let voter2_pubkey: Pubkey = voter2.pubkey();
+    output.note(&format!("Voter2 Pubkey: {}", voter2_pubkey));
 
-    let voter3_keypair: Keypair = read_keypair_file(VOTER3_KEY_FILE_PATH).unwrap();
-    let voter3_pubkey: Pubkey = voter3_keypair.pubkey();
-    println!("Voter3 Pubkey: {}", voter3_pubkey);
+    let voter3: Arc<dyn Signer> = signers::signer_from_path(VOTER3_KEY_FILE_PATH).unwrap();
+    let voter3_pubkey: Pubkey = voter3.pubkey();
+    output.note(&format!("Voter3 Pubkey: {}", voter3_pubkey));
 
-    let voter4_keypair: Keypair = read_keypair_file(VOTER4_KEY_FILE_PATH).unwrap();
-    let voter4_pubkey: Pubkey = voter4_keypair.pubkey();
-    println!("Voter4 Pubkey: {}", voter4_pubkey);
+    let voter4: Arc<dyn Signer> = signers::signer_from_path(VOTER4_KEY_FILE_PATH).unwrap();
+    let voter4_pubkey: Pubkey = voter4.pubkey();
+    output.note(&format!("Voter4 Pubkey: {}", voter4_pubkey));
 
-    let voter5_keypair: Keypair = read_keypair_file(VOTER5_KEY_FILE_PATH).unwrap();
-    let voter5_pubkey: Pubkey = voter5_keypair.pubkey();
-    println!("Voter5 Pubkey: {}", voter5_pubkey);
+    let voter5: Arc<dyn Signer> = signers::signer_from_path(VOTER5_KEY_FILE_PATH).unwrap();
+    let voter5_pubkey: Pubkey = voter5.pubkey();
+    output.note(&format!("Voter5 Pubkey: {}", voter5_pubkey));
 
     // let max_voter_weight_record_keypair: Keypair = read_keypair_file(MAX_VOTER_WEIGHT_RECORD_KEY_FILE_PATH).unwrap();
     // let max_voter_weight_record_pubkey: Pubkey = max_voter_weight_record_keypair.pubkey();
@@ -111,65 +633,124 @@ fn main() {
     // let voter3_weight_record_pubkey: Pubkey = voter3_weight_record_keypair.pubkey();
     // println!("Voter3 Weight Record Pubkey: {}", voter3_weight_record_pubkey);
 
-    let interactor = commands::SplGovernanceInteractor::new("http://localhost:8899", program_id, voter_weight_addin_pubkey);
-    // let interactor = commands::SplGovernanceInteractor::new("https://api.devnet.solana.com", program_id, voter_weight_addin_pubkey);
-
-    let realm: Realm = interactor.create_realm(owner_keypair, &community_pubkey, Some(voter_weight_addin_pubkey), REALM_NAME).unwrap();
-    println!("{:?}", realm);
-
-    println!("Realm Pubkey: {}", interactor.get_realm_address(REALM_NAME));
-
-    // let result = interactor.setup_max_voter_weight_record_mock(&realm, max_voter_weight_record_keypair, 10_000_000_000);
-    let result = interactor.setup_max_voter_weight_record_fixed(&realm);
-    println!("{:?}", result);
-
-    let (max_voter_weight_record_address,_) = get_max_voter_weight_address(&voter_weight_addin_pubkey, &realm.address, &community_pubkey);
-    println!("MaxVoterWeightRecord Pubkey {:?}", max_voter_weight_record_address);
-    let max_voter_weight_record = interactor.get_max_voter_weight_record(&max_voter_weight_record_address);
-    println!("{:?}", max_voter_weight_record);
-    // return;
-
-    let token_owner1: TokenOwner = interactor.create_token_owner_record(&realm, voter1_keypair).unwrap();
-    // let token_owner: TokenOwner = interactor.setup_voter_weight_record_mock(&realm, token_owner, voter_weight_record_keypair, 10_000_000_000).unwrap();
-    let token_owner1: TokenOwner = interactor.setup_voter_weight_record_fixed(&realm, token_owner1).unwrap();
-    println!("Token Owner 1 \n{:?}", token_owner1);
-
-    let (voter_weight_record_address,_) = get_voter_weight_address(&voter_weight_addin_pubkey, &realm.address, &community_pubkey, &token_owner1.authority.pubkey());
-    let voter_weight_record = interactor.get_voter_weight_record(&voter_weight_record_address);
-    println!("Token Owner 1 VoterWeightRecord \n{:?}", voter_weight_record);
+    let execution_mode: ExecutionMode =
+        match (args.dry_run, args.export) {
+            (_, Some(directory)) => ExecutionMode::Export(directory),
+            (true, None) => ExecutionMode::DryRun,
+            (false, None) => ExecutionMode::Send,
+        };
 
-    let token_owner2: TokenOwner = interactor.create_token_owner_record(&realm, voter2_keypair).unwrap();
-    // let token_owner2: TokenOwner = interactor.setup_voter_weight_record_mock(&realm, token_owner2, voter2_weight_record_keypair, 2_000_000_000).unwrap();
-    let token_owner2: TokenOwner = interactor.setup_voter_weight_record_fixed(&realm, token_owner2).unwrap();
-    println!("Token Owner 2 \n{:?}", token_owner2);
+    let send_strategy: SendStrategy =
+        SendStrategy {
+            retries: args.retries,
+            skip_preflight: args.skip_preflight,
+            commitment: args.commitment,
+            on_retry: Some(|error, attempt| eprintln!("Transaction failed ({}), retry {}", error, attempt) ),
+            ..SendStrategy::default()
+        };
 
-    let (voter_weight_record_address,_) = get_voter_weight_address(&voter_weight_addin_pubkey, &realm.address, &community_pubkey, &token_owner2.authority.pubkey());
-    let voter_weight_record = interactor.get_voter_weight_record(&voter_weight_record_address);
-    println!("Token Owner 2 VoterWeightRecord \n{:?}", voter_weight_record);
+    let interactor = commands::SplGovernanceInteractor::new(url, program_id, voter_weight_addin_pubkey, payer.clone())
+        .with_execution_mode(execution_mode)
+        .with_send_strategy(send_strategy)
+        .with_drift_policy(if args.fail_on_drift { DriftPolicy::Error } else { DriftPolicy::Warn })
+        .with_manifest(manifest_path.to_path_buf())
+        .unwrap();
+    // let interactor = commands::SplGovernanceInteractor::new("https://api.devnet.solana.com", program_id, voter_weight_addin_pubkey, payer.clone());
+
+    let interactor =
+        match (args.nonce_account, args.nonce_authority) {
+            (Some(account), Some(authority)) =>
+                interactor.with_durable_nonce(
+                    DurableNonce {
+                        account,
+                        authority: signers::signer_from_path(&authority).unwrap(),
+                    }
+                ),
+            _ => interactor,
+        };
 
-    let token_owner3: TokenOwner = interactor.create_token_owner_record(&realm, voter3_keypair).unwrap();
-    let token_owner3: TokenOwner = interactor.setup_voter_weight_record_fixed(&realm, token_owner3).unwrap();
-    println!("Token Owner 3 \n{:?}", token_owner3);
+    // Names of the manifest entries, e.g. "governance wBAL" and "token-owner-record voter1"
+    interactor.set_label(&governed_account_pubkey, "wBAL");
+    for (voter_pubkey, label) in [voter1_pubkey, voter2_pubkey, voter3_pubkey, voter4_pubkey, voter5_pubkey].iter().zip(["voter1", "voter2", "voter3", "voter4", "voter5"]) {
+        interactor.set_label(voter_pubkey, label);
+    }
 
-    let (voter_weight_record_address,_) = get_voter_weight_address(&voter_weight_addin_pubkey, &realm.address, &community_pubkey, &token_owner3.authority.pubkey());
-    let voter_weight_record = interactor.get_voter_weight_record(&voter_weight_record_address);
-    println!("Token Owner 3 VoterWeightRecord \n{:?}", voter_weight_record);
+    // Realm authority is also the first voter
+    let realm: Realm = check_sent(&interactor, &output, interactor.create_realm(voter1.clone(), &community_pubkey, Some(voter_weight_addin_pubkey), REALM_NAME))?;
+    let output: Output = get_output(&interactor, output, Some(community_pubkey));
+    output.print(&realm);
 
-    let token_owner4: TokenOwner = interactor.create_token_owner_record(&realm, voter4_keypair).unwrap();
-    let token_owner4: TokenOwner = interactor.setup_voter_weight_record_fixed(&realm, token_owner4).unwrap();
-    println!("Token Owner 4 \n{:?}", token_owner4);
+    output.note(&format!("Realm Pubkey: {}", interactor.get_realm_address(REALM_NAME)));
 
-    let (voter_weight_record_address,_) = get_voter_weight_address(&voter_weight_addin_pubkey, &realm.address, &community_pubkey, &token_owner4.authority.pubkey());
-    let voter_weight_record = interactor.get_voter_weight_record(&voter_weight_record_address);
-    println!("Token Owner 4 VoterWeightRecord \n{:?}", voter_weight_record);
+    // let result = interactor.setup_max_voter_weight_record_mock(&realm, max_voter_weight_record_keypair, 10_000_000_000);
+    match check_sent(&interactor, &output, interactor.setup_max_voter_weight_record_fixed(&realm))? {
+        Some(outcome) => output.note(&format!("MaxVoterWeightRecord: {}", outcome.to_string().trim_end())),
+        None => output.note("MaxVoterWeightRecord exists"),
+    }
 
-    let token_owner5: TokenOwner = interactor.create_token_owner_record(&realm, voter5_keypair).unwrap();
-    let token_owner5: TokenOwner = interactor.setup_voter_weight_record_fixed(&realm, token_owner5).unwrap();
-    println!("Token Owner 5 \n{:?}", token_owner4);
+    let max_voter_weight_record_address: Pubkey = check_sent(&interactor, &output, interactor.get_recorded_address(AccountKind::MaxVoterWeightRecord, REALM_NAME))?;
+    output.note(&format!("MaxVoterWeightRecord Pubkey {:?}", max_voter_weight_record_address));
+    let max_voter_weight_record = check_sent(&interactor, &output, interactor.get_max_voter_weight_record(&max_voter_weight_record_address))?;
+    output.print(&max_voter_weight_record);
+    // return;
 
-    let (voter_weight_record_address,_) = get_voter_weight_address(&voter_weight_addin_pubkey, &realm.address, &community_pubkey, &token_owner5.authority.pubkey());
-    let voter_weight_record = interactor.get_voter_weight_record(&voter_weight_record_address);
-    println!("Token Owner 5 VoterWeightRecord \n{:?}", voter_weight_record);
+    // Token owner records and voter weight records of all voters in as few transactions as possible
+    let mut batch = interactor.batch();
+    for voter_pubkey in [voter1_pubkey, voter2_pubkey, voter3_pubkey, voter4_pubkey, voter5_pubkey].iter() {
+        batch
+            .create_token_owner_record(&realm, voter_pubkey)
+            .setup_voter_weight_record_fixed(&realm, voter_pubkey);
+    }
+    let result = batch.execute();
+    output.note(&format!("Voters Setup {:?}", result));
+
+    // Records created by the batch above
+    // let token_owner: TokenOwner = interactor.setup_voter_weight_record_mock(&realm, token_owner, voter_weight_record_keypair, 10_000_000_000, None).unwrap();
+    let token_owner1: TokenOwner = check_sent(&interactor, &output, interactor.get_token_owner(&realm, voter1.clone()))?;
+    output.note("Token Owner 1");
+    output.print(&token_owner1);
+
+    let voter_weight_record_address: Pubkey = check_sent(&interactor, &output, interactor.get_recorded_address(AccountKind::VoterWeightRecord, "voter1"))?;
+    let voter_weight_record = check_sent(&interactor, &output, interactor.get_voter_weight_record(&voter_weight_record_address))?;
+    output.note("Token Owner 1 VoterWeightRecord");
+    output.print(&voter_weight_record);
+
+    // let token_owner2: TokenOwner = interactor.setup_voter_weight_record_mock(&realm, token_owner2, voter2_weight_record_keypair, 2_000_000_000, None).unwrap();
+    let token_owner2: TokenOwner = check_sent(&interactor, &output, interactor.get_token_owner(&realm, voter2.clone()))?;
+    output.note("Token Owner 2");
+    output.print(&token_owner2);
+
+    let voter_weight_record_address: Pubkey = check_sent(&interactor, &output, interactor.get_recorded_address(AccountKind::VoterWeightRecord, "voter2"))?;
+    let voter_weight_record = check_sent(&interactor, &output, interactor.get_voter_weight_record(&voter_weight_record_address))?;
+    output.note("Token Owner 2 VoterWeightRecord");
+    output.print(&voter_weight_record);
+
+    let token_owner3: TokenOwner = check_sent(&interactor, &output, interactor.get_token_owner(&realm, voter3.clone()))?;
+    output.note("Token Owner 3");
+    output.print(&token_owner3);
+
+    let voter_weight_record_address: Pubkey = check_sent(&interactor, &output, interactor.get_recorded_address(AccountKind::VoterWeightRecord, "voter3"))?;
+    let voter_weight_record = check_sent(&interactor, &output, interactor.get_voter_weight_record(&voter_weight_record_address))?;
+    output.note("Token Owner 3 VoterWeightRecord");
+    output.print(&voter_weight_record);
+
+    let token_owner4: TokenOwner = check_sent(&interactor, &output, interactor.get_token_owner(&realm, voter4.clone()))?;
+    output.note("Token Owner 4");
+    output.print(&token_owner4);
+
+    let voter_weight_record_address: Pubkey = check_sent(&interactor, &output, interactor.get_recorded_address(AccountKind::VoterWeightRecord, "voter4"))?;
+    let voter_weight_record = check_sent(&interactor, &output, interactor.get_voter_weight_record(&voter_weight_record_address))?;
+    output.note("Token Owner 4 VoterWeightRecord");
+    output.print(&voter_weight_record);
+
+    let token_owner5: TokenOwner = check_sent(&interactor, &output, interactor.get_token_owner(&realm, voter5.clone()))?;
+    output.note("Token Owner 5");
+    output.print(&token_owner5);
+
+    let voter_weight_record_address: Pubkey = check_sent(&interactor, &output, interactor.get_recorded_address(AccountKind::VoterWeightRecord, "voter5"))?;
+    let voter_weight_record = check_sent(&interactor, &output, interactor.get_voter_weight_record(&voter_weight_record_address))?;
+    output.note("Token Owner 5 VoterWeightRecord");
+    output.print(&voter_weight_record);
 
     let gov_config: GovernanceConfig =
         GovernanceConfig {
@@ -182,8 +763,8 @@ fn main() {
             min_council_weight_to_create_proposal: 0,
         };
 
-    let governance: Governance = interactor.create_governance(&realm, &token_owner1, &governed_account_pubkey, gov_config).unwrap();
-    println!("{:?}", governance);
+    let governance: Governance = check_sent(&interactor, &output, interactor.create_governance(&realm, &token_owner1, &governed_account_pubkey, gov_config.clone()))?;
+    output.print(&governance);
 
     let proposal_number: u32 = 
         if governance.get_proposal_count() > 0 {
@@ -192,34 +773,70 @@ fn main() {
         } else {
             0
         };
-    let proposal: Proposal = interactor.create_proposal(&realm, &token_owner1, &governance, PROPOSAL_NAME, PROPOSAL_DESCRIPTION, proposal_number).unwrap();
-    println!("{:?}", proposal);
+    let proposal: Proposal = check_sent(&interactor, &output, interactor.create_proposal(&realm, &token_owner1, &governance, PROPOSAL_NAME, PROPOSAL_DESCRIPTION, proposal_number))?;
+    // let proposal: Proposal = interactor.create_proposal_with_options(&realm, &token_owner1, &governance, PROPOSAL_NAME, PROPOSAL_DESCRIPTION, proposal_number,
+    //         VoteType::MultiChoice(2), vec!["Option A".to_string(), "Option B".to_string(), "Option C".to_string()], false).unwrap();
+    output.print(&proposal);
 
-    // let result = interactor.add_signatory(&realm, &governance, &proposal, &token_owner);
+    // let result = interactor.add_signatory(&proposal, &token_owner1, &token_owner2.get_owner_pubkey());
     // println!("Add signatory {:?}", result);
 
     let proposal: Proposal = 
         if proposal.data.state == ProposalState::Draft {
-            interactor.sign_off_proposal(&realm, &governance, proposal, &token_owner1).unwrap()
+            check_sent(&interactor, &output, interactor.sign_off_proposal(&realm, &governance, proposal, &token_owner1))?
         } else {
             proposal
         };
-    println!("{:?}\n", proposal);
+    output.print(&proposal);
+    output.note("");
+
+    // Expected outcome of every vote, compared with the on-chain proposal
+    let max_vote_weight: u64 = check_sent(&interactor, &output, interactor.get_max_vote_weight(&realm))?;
+    let mut tally: VoteTally =
+        match VoteTally::new(&gov_config, max_vote_weight, &proposal.data) {
+            Ok(tally) => tally,
+            Err(err) => {
+                eprintln!("Vote tally: {}", err);
+                return None;
+            },
+        };
 
     // // let result = interactor.cast_vote(&realm, &governance, &proposal, &token_owner, Some(max_voter_weight_record_pubkey), true);
-    let result = interactor.cast_vote(&realm, &governance, &proposal, &token_owner1, true);
-    println!("{:?}", result);
+    let result = cast_vote_and_compare(&interactor, &mut tally, &realm, &governance, &proposal, &token_owner1, yes_no_vote(true));
+    output.note(&format!("{:?}", result));
 
-    let result = interactor.cast_vote(&realm, &governance, &proposal, &token_owner2, false);
-    println!("{:?}", result);
+    let result = cast_vote_and_compare(&interactor, &mut tally, &realm, &governance, &proposal, &token_owner2, yes_no_vote(false));
+    output.note(&format!("{:?}", result));
 
     // let result = interactor.cast_vote(&realm, &governance, &proposal, &token_owner3, false);
     // println!("{:?}", result);
 
+    // let result = interactor.set_governance_delegate(&realm, &token_owner3, Some(voter4_pubkey));
+    // println!("Set Governance Delegate {:?}", result);
+    // let delegated_owner3: TokenOwner = interactor.get_delegated_token_owner(&realm, &voter3_pubkey, voter4.clone()).unwrap();
+    // let result = interactor.cast_vote(&realm, &governance, &proposal, &delegated_owner3, false);
+    // println!("{:?}", result);
+
     // let result = interactor.cast_vote(&realm, &governance, &proposal, &token_owner4, true);
     // println!("{:?}", result);
 
     // let result = interactor.cast_vote(&realm, &governance, &proposal, &token_owner5, true);
     // println!("{:?}", result);
 
+    // let result = interactor.cast_vote_choices(&realm, &governance, &proposal, &token_owner3, vec![
+    //         VoteChoice { rank: 0, weight_percentage: 100 },
+    //         VoteChoice { rank: 0, weight_percentage: 0 },
+    //         VoteChoice { rank: 0, weight_percentage: 100 },
+    //     ]);
+    // println!("{:?}", result);
+
+    let proposal: Proposal = check_sent(&interactor, &output, interactor.reload_proposal(&proposal))?;
+    output.note(&format!("Option Vote Weights: {:?}, Deny Vote Weight: {:?}", proposal.get_option_vote_weights(), proposal.get_deny_vote_weight()));
+
+    for outcome in interactor.take_outcomes() {
+        output.note(&outcome.to_string());
+    }
+    print_drifts(&interactor, &output);
+
+    Some(())
 }
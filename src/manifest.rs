@@ -0,0 +1,131 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    fs,
+    path::{ Path },
+    str::FromStr,
+};
+
+use serde::{ Deserialize, Serialize };
+
+use solana_sdk::pubkey::{ Pubkey };
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccountKind {
+    Realm,
+    TokenOwnerRecord,
+    VoterWeightRecord,
+    MaxVoterWeightRecord,
+    Governance,
+    Proposal,
+    ProposalTransaction,
+}
+
+impl fmt::Display for AccountKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name: &str =
+            match self {
+                AccountKind::Realm => "realm",
+                AccountKind::TokenOwnerRecord => "token-owner-record",
+                AccountKind::VoterWeightRecord => "voter-weight-record",
+                AccountKind::MaxVoterWeightRecord => "max-voter-weight-record",
+                AccountKind::Governance => "governance",
+                AccountKind::Proposal => "proposal",
+                AccountKind::ProposalTransaction => "proposal-transaction",
+            };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub kind: AccountKind,
+    pub address: String,
+}
+
+/// Accounts created or found by the interactor, keyed by logical names such as `governance wBAL`
+/// or `proposal wBAL 3`, stored as JSON between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Labels used instead of pubkeys in the names, e.g. `wBAL` for the governed mint
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self,Box<dyn Error>> {
+        let json: String = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Empty manifest if there is no file yet
+    pub fn load_or_default(path: &Path) -> Result<Self,Box<dyn Error>> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Manifest::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(),Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn set_label(&mut self, pubkey: &Pubkey, label: &str) {
+        self.labels.insert(pubkey.to_string(), label.to_string());
+    }
+
+    /// Label of `pubkey`, the pubkey itself if it has none
+    pub fn get_label(&self, pubkey: &Pubkey) -> String {
+        self.labels.get(&pubkey.to_string())
+            .cloned()
+            .unwrap_or_else(|| pubkey.to_string() )
+    }
+
+    /// Records `address` as `<kind> <suffix>`, returns the name
+    pub fn insert(&mut self, kind: AccountKind, suffix: &str, address: &Pubkey) -> String {
+        let name: String = get_account_name(kind, suffix);
+        self.accounts.insert(
+            name.clone(),
+            ManifestEntry {
+                kind,
+                address: address.to_string(),
+            }
+        );
+        name
+    }
+
+    pub fn get_address(&self, name: &str) -> Option<Pubkey> {
+        self.accounts.get(name)
+            .and_then(|entry| Pubkey::from_str(&entry.address).ok() )
+    }
+
+    /// Name under which `address` is recorded
+    pub fn get_name(&self, address: &Pubkey) -> Option<String> {
+        let address: String = address.to_string();
+        self.accounts.iter()
+            .find(|(_, entry)| entry.address == address )
+            .map(|(name, _)| name.clone() )
+    }
+
+    /// Address recorded by `insert(kind, suffix, ..)`
+    pub fn get_recorded_address(&self, kind: AccountKind, suffix: &str) -> Option<Pubkey> {
+        self.get_address(&get_account_name(kind, suffix))
+    }
+
+    pub fn get_entries(&self, kind: AccountKind) -> Vec<(&String, &ManifestEntry)> {
+        self.accounts.iter()
+            .filter(|(_, entry)| entry.kind == kind )
+            .collect()
+    }
+}
+
+/// Manifest name of an account, e.g. `governance wBAL`
+pub fn get_account_name(kind: AccountKind, suffix: &str) -> String {
+    format!("{} {}", kind, suffix)
+}
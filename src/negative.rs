@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use num_traits::FromPrimitive;
+
+use solana_sdk::{
+    clock::{ Clock },
+    instruction::{ AccountMeta, Instruction, InstructionError },
+    pubkey::{ Pubkey },
+    signature::{ Signature },
+    signer::{
+        Signer,
+        keypair::{ Keypair },
+    },
+    system_instruction,
+    transaction::{ TransactionError },
+};
+
+use solana_client::client_error::Result as ClientResult;
+use solana_client::client_error::ClientError;
+
+use spl_governance::{
+    error::{ GovernanceError },
+    instruction::{ sign_off_proposal },
+    state::{
+        enums::{
+            VoteThresholdPercentage,
+            VoteTipping,
+        },
+        governance::{
+            GovernanceConfig,
+        },
+        signatory_record::{ get_signatory_record_address },
+    },
+};
+
+use crate::program_test::{ ProgramTestRealm, create_mock_voter };
+use crate::commands::{ SplGovernanceInteractor, Outcome, Realm, Governance, Proposal, TokenOwner };
+
+const REALM_NAME: &'static str = "Negative Scenarios";
+const MAX_VOTER_WEIGHT: u64 = 100;
+/// Two voters above `min_community_weight_to_create_proposal` which together pass the 60% threshold, one below it
+const VOTER_WEIGHTS: [u64; 3] = [40, 30, 5];
+const MIN_COMMUNITY_WEIGHT_TO_CREATE_PROPOSAL: u64 = 10;
+const MAX_VOTING_TIME: u32 = 3600;
+const HOLD_UP_TIME: u32 = 3600;
+
+/// Operation the governance program must reject with `expected_error`
+pub struct NegativeScenario {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub expected_error: GovernanceError,
+    /// Outer `Err` if the setup before the operation under test failed
+    operation: fn(&ScenarioContext) -> ClientResult<ClientResult<Outcome>>,
+}
+
+pub fn catalog() -> Vec<NegativeScenario> {
+    vec![
+        NegativeScenario {
+            name: "vote-twice",
+            description: "Voter casts a second vote on the same proposal",
+            expected_error: GovernanceError::VoteAlreadyExists,
+            operation: vote_twice,
+        },
+        NegativeScenario {
+            name: "vote-after-voting-time",
+            description: "Vote is cast after max_voting_time has passed",
+            expected_error: GovernanceError::ProposalVotingTimeExpired,
+            operation: vote_after_voting_time,
+        },
+        NegativeScenario {
+            name: "proposal-below-min-weight",
+            description: "Proposal is created by a voter below min_community_weight_to_create_proposal",
+            expected_error: GovernanceError::NotEnoughTokensToCreateProposal,
+            operation: proposal_below_min_weight,
+        },
+        NegativeScenario {
+            name: "sign-off-by-non-signatory",
+            description: "Proposal with an added signatory is signed off by a token owner without a signatory record, using the record of that signatory",
+            expected_error: GovernanceError::InvalidSignatoryAddress,
+            operation: sign_off_by_non_signatory,
+        },
+        NegativeScenario {
+            name: "execute-within-hold-up-time",
+            description: "Transaction of a succeeded proposal is executed before its hold-up time has passed",
+            expected_error: GovernanceError::CannotExecuteTransactionWithinHoldUpTime,
+            operation: execute_within_hold_up_time,
+        },
+        NegativeScenario {
+            name: "vote-with-expired-voter-weight",
+            description: "Vote uses a voter weight record whose expiry slot has passed",
+            expected_error: GovernanceError::VoterWeightRecordExpired,
+            operation: vote_with_expired_voter_weight,
+        },
+    ]
+}
+
+impl NegativeScenario {
+    /// Runs the scenario on a fresh in-process bank. `Err` describes how the outcome differs from `expected_error`
+    pub fn run(&self) -> Result<(),String> {
+        let context: ScenarioContext = ScenarioContext::new().map_err(|err| format!("setup failed: {}", err) )?;
+        let result: ClientResult<Outcome> = (self.operation)(&context).map_err(|err| format!("setup failed: {}", err) )?;
+
+        match result {
+            Ok(outcome) => Err(format!("succeeded ({}) instead of failing with {:?}", outcome.to_string().trim_end(), self.expected_error)),
+            Err(error) => {
+                match get_governance_error(&error) {
+                    Some(governance_error) if governance_error == self.expected_error => Ok(()),
+                    Some(governance_error) => Err(format!("failed with {:?} instead of {:?}", governance_error, self.expected_error)),
+                    None => Err(format!("failed with {} instead of {:?}", error, self.expected_error)),
+                }
+            },
+        }
+    }
+}
+
+/// Governance program error of a failed transaction (custom instruction error)
+pub fn get_governance_error(error: &ClientError) -> Option<GovernanceError> {
+    match error.get_transaction_error() {
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => GovernanceError::from_u32(code),
+        _ => None,
+    }
+}
+
+/// Realm on the mock voter weight addin with `VOTER_WEIGHTS` voters
+struct ScenarioContext {
+    interactor: SplGovernanceInteractor,
+    realm: Realm,
+    voters: Vec<TokenOwner>,
+}
+
+impl ScenarioContext {
+    fn new() -> ClientResult<Self> {
+        let ProgramTestRealm { interactor, realm, voters } = ProgramTestRealm::new_mock(REALM_NAME, MAX_VOTER_WEIGHT, &VOTER_WEIGHTS)?;
+        Ok(
+            ScenarioContext {
+                interactor,
+                realm,
+                voters,
+            }
+        )
+    }
+
+    fn create_voter(&self, voter: Arc<dyn Signer>, voter_weight: u64, voter_weight_expiry: Option<u64>) -> ClientResult<TokenOwner> {
+        create_mock_voter(&self.interactor, &self.realm, voter, voter_weight, voter_weight_expiry)
+    }
+
+    fn create_governance(&self, min_transaction_hold_up_time: u32) -> ClientResult<Governance> {
+        let gov_config: GovernanceConfig =
+            GovernanceConfig {
+                vote_threshold_percentage: VoteThresholdPercentage::YesVote(60),
+                min_community_weight_to_create_proposal: MIN_COMMUNITY_WEIGHT_TO_CREATE_PROPOSAL,
+                min_transaction_hold_up_time,
+                max_voting_time: MAX_VOTING_TIME,
+                vote_tipping: VoteTipping::Strict,
+                proposal_cool_off_time: 0,
+                min_council_weight_to_create_proposal: 0,
+            };
+
+        self.interactor.create_governance(&self.realm, &self.voters[0], &Pubkey::new_unique(), gov_config)
+    }
+
+    fn create_proposal(&self, governance: &Governance, owner: &TokenOwner) -> ClientResult<Proposal> {
+        self.interactor.create_proposal(&self.realm, owner, governance, "Negative Scenario", "", governance.get_proposal_count())
+    }
+
+    /// Governance with a proposal of the first voter in voting state
+    fn create_voting_proposal(&self) -> ClientResult<(Governance, Proposal)> {
+        let governance: Governance = self.create_governance(0)?;
+        let proposal: Proposal = self.create_proposal(&governance, &self.voters[0])?;
+        let proposal: Proposal = self.interactor.sign_off_proposal(&self.realm, &governance, proposal, &self.voters[0])?;
+        Ok((governance, proposal))
+    }
+}
+
+fn vote_twice(context: &ScenarioContext) -> ClientResult<ClientResult<Outcome>> {
+    let (governance, proposal) = context.create_voting_proposal()?;
+    context.interactor.cast_vote(&context.realm, &governance, &proposal, &context.voters[0], true)?;
+    // Different vote: an identical transaction would be rejected by the runtime as already processed
+    Ok(context.interactor.cast_vote(&context.realm, &governance, &proposal, &context.voters[0], false))
+}
+
+fn vote_after_voting_time(context: &ScenarioContext) -> ClientResult<ClientResult<Outcome>> {
+    let (governance, proposal) = context.create_voting_proposal()?;
+    context.interactor.advance_past_voting_end(&governance, &proposal)?;
+    Ok(context.interactor.cast_vote(&context.realm, &governance, &proposal, &context.voters[0], true))
+}
+
+fn proposal_below_min_weight(context: &ScenarioContext) -> ClientResult<ClientResult<Outcome>> {
+    let governance: Governance = context.create_governance(0)?;
+    Ok(
+        context.create_proposal(&governance, &context.voters[2])
+            .map(|_| Outcome::Sent(Signature::default()) )
+    )
+}
+
+fn sign_off_by_non_signatory(context: &ScenarioContext) -> ClientResult<ClientResult<Outcome>> {
+    let governance: Governance = context.create_governance(0)?;
+    let proposal: Proposal = context.create_proposal(&governance, &context.voters[0])?;
+    let signatory: &TokenOwner = &context.voters[1];
+    context.interactor.add_signatory(&proposal, &context.voters[0], &signatory.get_owner_pubkey())?;
+
+    // The impostor has a token owner record but no signatory record: it presents the one of the signatory
+    let impostor: &TokenOwner = &context.voters[2];
+    let mut sign_off_proposal_instruction: Instruction =
+        sign_off_proposal(
+            &context.interactor.get_program_address(),
+            &context.realm.address,
+            &governance.address,
+            &proposal.address,
+            &impostor.get_owner_pubkey(),
+            None,
+        );
+    sign_off_proposal_instruction.accounts[4] =
+        AccountMeta::new(get_signatory_record_address(&context.interactor.get_program_address(), &proposal.address, &signatory.get_owner_pubkey()), false);
+
+    Ok(context.interactor.process_transaction(&[ sign_off_proposal_instruction ], &[ impostor.authority.as_ref() ]))
+}
+
+fn execute_within_hold_up_time(context: &ScenarioContext) -> ClientResult<ClientResult<Outcome>> {
+    let governance: Governance = context.create_governance(HOLD_UP_TIME)?;
+    let proposal: Proposal = context.create_proposal(&governance, &context.voters[0])?;
+    let transfer_instruction: Instruction = system_instruction::transfer(&governance.address, &context.interactor.get_payer_pubkey(), 1);
+    let proposal_transaction_address: Pubkey =
+        context.interactor.insert_transaction(&governance, &proposal, &context.voters[0], 0, HOLD_UP_TIME, vec![ transfer_instruction ])?;
+    let proposal: Proposal = context.interactor.sign_off_proposal(&context.realm, &governance, proposal, &context.voters[0])?;
+
+    // 40 + 30 of 100 tips the proposal to succeeded
+    context.interactor.cast_vote(&context.realm, &governance, &proposal, &context.voters[0], true)?;
+    context.interactor.cast_vote(&context.realm, &governance, &proposal, &context.voters[1], true)?;
+
+    Ok(context.interactor.execute_transaction(&governance, &proposal, &proposal_transaction_address))
+}
+
+fn vote_with_expired_voter_weight(context: &ScenarioContext) -> ClientResult<ClientResult<Outcome>> {
+    let (governance, proposal) = context.create_voting_proposal()?;
+    let clock: Clock = context.interactor.get_clock()?;
+    let voter: TokenOwner = context.create_voter(Arc::new(Keypair::new()), VOTER_WEIGHTS[0], Some(clock.slot))?;
+    context.interactor.advance_clock(1)?;
+    Ok(context.interactor.cast_vote(&context.realm, &governance, &proposal, &voter, true))
+}
@@ -0,0 +1,137 @@
+use std::{
+    error::Error,
+    fs,
+    path::{ Path },
+    str::FromStr,
+    sync::Arc,
+};
+
+use serde::{ Deserialize, Serialize };
+
+use solana_sdk::{
+    hash::{ Hash },
+    message::{ Message },
+    pubkey::{ Pubkey },
+    signature::{ Signature },
+    signer::{ Signer },
+    transaction::{ Transaction },
+};
+
+use solana_client::client_error::Result as ClientResult;
+
+use crate::backend::Backend;
+use crate::send::SendStrategy;
+
+/// Durable nonce used instead of a recent blockhash: the transaction starts with `advance_nonce_account`
+/// and stays valid until the nonce is advanced
+#[derive(Clone)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Arc<dyn Signer>,
+}
+
+/// Transaction message waiting for signatures, stored as JSON so it can travel to an air-gapped signer
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OfflineTransaction {
+    /// Base64 of the serialized message
+    pub message: String,
+    pub blockhash: String,
+    pub nonce_account: Option<String>,
+    /// Required signers in signature order
+    pub signers: Vec<String>,
+    pub signatures: Vec<Option<String>>,
+}
+
+impl OfflineTransaction {
+
+    /// Keeps the signatures already present in `transaction`, others are left for `sign`
+    pub fn from_transaction(transaction: &Transaction, nonce_account: Option<&Pubkey>) -> Self {
+        let required_signatures: usize = transaction.message.header.num_required_signatures as usize;
+
+        OfflineTransaction {
+            message: base64::encode(transaction.message.serialize()),
+            blockhash: transaction.message.recent_blockhash.to_string(),
+            nonce_account: nonce_account.map(|pubkey| pubkey.to_string() ),
+            signers:
+                transaction.message.account_keys[..required_signatures].iter()
+                    .map(|pubkey| pubkey.to_string() )
+                    .collect(),
+            signatures:
+                transaction.signatures.iter()
+                    .map(|signature| if *signature == Signature::default() { None } else { Some(signature.to_string()) } )
+                    .collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self,Box<dyn Error>> {
+        let json: String = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(),Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get_message(&self) -> Result<Message,Box<dyn Error>> {
+        let message_bytes: Vec<u8> = base64::decode(&self.message)?;
+        Ok(bincode::deserialize(&message_bytes)?)
+    }
+
+    pub fn get_blockhash(&self) -> Result<Hash,Box<dyn Error>> {
+        Ok(Hash::from_str(&self.blockhash)?)
+    }
+
+    pub fn missing_signers(&self) -> Vec<&str> {
+        self.signers.iter()
+            .zip(self.signatures.iter())
+            .filter(|(_, signature)| signature.is_none() )
+            .map(|(signer, _)| signer.as_str() )
+            .collect()
+    }
+
+    /// Adds the signature of `signer`, which must be one of the required signers
+    pub fn sign(&mut self, signer: &dyn Signer) -> Result<Signature,Box<dyn Error>> {
+        let signer_pubkey: String = signer.try_pubkey()?.to_string();
+        let position: usize =
+            self.signers.iter()
+                .position(|pubkey| *pubkey == signer_pubkey )
+                .ok_or_else(|| format!("{} is not a required signer of this transaction", signer_pubkey) )?;
+
+        let message_bytes: Vec<u8> = base64::decode(&self.message)?;
+        let signature: Signature = signer.try_sign_message(&message_bytes)?;
+        self.signatures[position] = Some(signature.to_string());
+
+        Ok(signature)
+    }
+
+    /// Assembles the signed transaction, failing if a signature is missing or invalid
+    pub fn to_transaction(&self) -> Result<Transaction,Box<dyn Error>> {
+        let missing_signers: Vec<&str> = self.missing_signers();
+        if !missing_signers.is_empty() {
+            return Err(format!("Missing signatures of {}", missing_signers.join(", ")).into());
+        }
+
+        let signatures: Vec<Signature> =
+            self.signatures.iter()
+                .map(|signature| Signature::from_str(signature.as_ref().unwrap()) )
+                .collect::<Result<_,_>>()?;
+
+        let transaction: Transaction =
+            Transaction {
+                signatures,
+                message: self.get_message()?,
+            };
+        transaction.verify()?;
+
+        Ok(transaction)
+    }
+}
+
+/// Sends the fully signed transaction once: it cannot be signed again with a fresh blockhash,
+/// so only the transport failures the backend handles itself are retried
+pub fn submit(backend: &dyn Backend, send_strategy: &SendStrategy, offline_transaction: &OfflineTransaction) -> Result<Signature,Box<dyn Error>> {
+    let transaction: Transaction = offline_transaction.to_transaction()?;
+    let result: ClientResult<Signature> = backend.send_and_confirm_transaction(&transaction, send_strategy, offline_transaction.nonce_account.is_some());
+    Ok(result?)
+}
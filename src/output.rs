@@ -0,0 +1,438 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde_json::{ Map, Value };
+
+use solana_sdk::{
+    clock::{ Slot, UnixTimestamp },
+    pubkey::{ Pubkey },
+    signer::Signer,
+};
+
+use spl_governance::state::{
+    governance::GovernanceV2,
+    proposal::ProposalV2,
+    realm::RealmV2,
+    token_owner_record::TokenOwnerRecordV2,
+    vote_record::VoteRecordV2,
+};
+
+use spl_governance_addin_api::{
+    max_voter_weight::MaxVoterWeightRecord,
+    voter_weight::VoterWeightRecord,
+};
+
+use crate::address_book::AddressBook;
+use crate::commands::{ Realm, Governance, Proposal, TokenOwner };
+
+/// Selected by `--output`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Aligned `field  value` lines with UI amounts and dates
+    Table,
+    /// Raw integers and pubkeys as strings, keys sorted, for scripting
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self,Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown output format {}, expected table or json", s)),
+        }
+    }
+}
+
+/// Named fields of an account, each with its table text and its JSON value
+pub struct Fields {
+    decimals: Option<u8>,
+    address_book: Option<Arc<AddressBook>>,
+    fields: Vec<(&'static str, String, Value)>,
+}
+
+impl Fields {
+    fn new(decimals: Option<u8>, address_book: Option<Arc<AddressBook>>) -> Self {
+        Fields {
+            decimals,
+            address_book,
+            fields: Vec::new(),
+        }
+    }
+
+    fn field(mut self, name: &'static str, text: String, value: Value) -> Self {
+        self.fields.push((name, text, value));
+        self
+    }
+
+    pub fn text(self, name: &'static str, text: &str) -> Self {
+        self.field(name, text.to_string(), Value::from(text))
+    }
+
+    /// Pubkey with its address book name in tables, bare in JSON
+    pub fn pubkey(self, name: &'static str, pubkey: &Pubkey) -> Self {
+        let text: String = match &self.address_book {
+            Some(address_book) => address_book.annotate(pubkey),
+            None => pubkey.to_string(),
+        };
+        self.field(name, text, Value::from(pubkey.to_string()))
+    }
+
+    pub fn optional_pubkey(self, name: &'static str, pubkey: Option<Pubkey>) -> Self {
+        match pubkey {
+            Some(pubkey) => self.pubkey(name, &pubkey),
+            None => self.field(name, "-".to_string(), Value::Null),
+        }
+    }
+
+    pub fn number(self, name: &'static str, number: u64) -> Self {
+        self.field(name, number.to_string(), Value::from(number))
+    }
+
+    pub fn flag(self, name: &'static str, flag: bool) -> Self {
+        self.field(name, flag.to_string(), Value::from(flag))
+    }
+
+    /// UI amount if the mint decimals are known, raw amount otherwise
+    fn format_amount(&self, amount: u64) -> String {
+        match self.decimals {
+            Some(decimals) => format_ui_amount(amount, decimals),
+            None => amount.to_string(),
+        }
+    }
+
+    /// Token amount: UI amount in tables if the mint decimals are known, raw amount in JSON
+    pub fn amount(self, name: &'static str, amount: u64) -> Self {
+        let text: String = self.format_amount(amount);
+        self.field(name, text, Value::from(amount))
+    }
+
+    pub fn optional_amount(self, name: &'static str, amount: Option<u64>) -> Self {
+        match amount {
+            Some(amount) => self.amount(name, amount),
+            None => self.field(name, "-".to_string(), Value::Null),
+        }
+    }
+
+    /// UTC date in tables, unix timestamp in JSON
+    pub fn timestamp(self, name: &'static str, timestamp: Option<UnixTimestamp>) -> Self {
+        match timestamp {
+            Some(timestamp) => self.field(name, format_timestamp(timestamp), Value::from(timestamp)),
+            None => self.field(name, "-".to_string(), Value::Null),
+        }
+    }
+
+    pub fn slot(self, name: &'static str, slot: Option<Slot>) -> Self {
+        match slot {
+            Some(slot) => self.number(name, slot),
+            None => self.field(name, "-".to_string(), Value::Null),
+        }
+    }
+
+    /// Enum state or config, e.g. `Voting` or `YesVote(60)`, by its `Debug` name
+    pub fn debug<T: Debug>(self, name: &'static str, value: &T) -> Self {
+        let text: String = format!("{:?}", value);
+        self.field(name, text.clone(), Value::from(text))
+    }
+
+    pub fn to_json(&self) -> Value {
+        let object: Map<String, Value> =
+            self.fields.iter()
+                .map(|(name, _, value)| (name.to_string(), value.clone()) )
+                .collect();
+        Value::Object(object)
+    }
+
+    pub fn to_table(&self) -> String {
+        let width: usize = self.fields.iter().map(|(name, _, _)| name.len() ).max().unwrap_or(0);
+        self.fields.iter()
+            .map(|(name, text, _)| format!("{:width$}  {}\n", name, text, width = width) )
+            .collect()
+    }
+}
+
+/// Account that can be printed by `Output`
+pub trait Describe {
+    fn describe(&self, fields: Fields) -> Fields;
+}
+
+/// Renders accounts in the selected format; amounts use the community mint decimals if set
+#[derive(Debug, Clone)]
+pub struct Output {
+    format: OutputFormat,
+    decimals: Option<u8>,
+    address_book: Option<Arc<AddressBook>>,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Self {
+        Output {
+            format,
+            decimals: None,
+            address_book: None,
+        }
+    }
+
+    pub fn with_decimals(mut self, decimals: u8) -> Self {
+        self.decimals = Some(decimals);
+        self
+    }
+
+    pub fn with_address_book(mut self, address_book: Arc<AddressBook>) -> Self {
+        self.address_book = Some(address_book);
+        self
+    }
+
+    pub fn get_format(&self) -> OutputFormat {
+        self.format
+    }
+
+    pub fn get_fields<T: Describe + ?Sized>(&self, item: &T) -> Fields {
+        item.describe(Fields::new(self.decimals, self.address_book.clone()))
+    }
+
+    pub fn render<T: Describe + ?Sized>(&self, item: &T) -> String {
+        let fields: Fields = self.get_fields(item);
+        match self.format {
+            OutputFormat::Table => fields.to_table(),
+            OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(&fields.to_json()).unwrap()),
+        }
+    }
+
+    /// Blank-line separated tables, or one JSON array
+    pub fn render_list<T: Describe>(&self, items: &[T]) -> String {
+        match self.format {
+            OutputFormat::Table => {
+                items.iter()
+                    .map(|item| self.get_fields(item).to_table() )
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            },
+            OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(&self.get_list_json(items)).unwrap()),
+        }
+    }
+
+    pub fn get_list_json<T: Describe>(&self, items: &[T]) -> Value {
+        Value::Array(items.iter().map(|item| self.get_fields(item).to_json() ).collect())
+    }
+
+    pub fn print<T: Describe + ?Sized>(&self, item: &T) {
+        print!("{}", self.render(item));
+    }
+
+    pub fn print_list<T: Describe>(&self, items: &[T]) {
+        print!("{}", self.render_list(items));
+    }
+
+    /// Text around the rendered accounts, with its pubkeys annotated by the address book:
+    /// on stdout with tables, on stderr with JSON so stdout stays parseable
+    pub fn note(&self, text: &str) {
+        let text: String = match &self.address_book {
+            Some(address_book) => address_book.annotate_text(text),
+            None => text.to_string(),
+        };
+        match self.format {
+            OutputFormat::Table => println!("{}", text),
+            OutputFormat::Json => eprintln!("{}", text),
+        }
+    }
+}
+
+/// `amount` divided by 10^`decimals`, without trailing zeros
+pub fn format_ui_amount(amount: u64, decimals: u8) -> String {
+    let divisor: u128 = 10u128.pow(decimals as u32);
+    let integer: u128 = amount as u128 / divisor;
+    let fraction: u128 = amount as u128 % divisor;
+    if fraction == 0 {
+        return integer.to_string();
+    }
+    let fraction: String = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", integer, fraction.trim_end_matches('0'))
+}
+
+/// `YYYY-MM-DD hh:mm:ss UTC`
+pub fn format_timestamp(timestamp: UnixTimestamp) -> String {
+    let days: i64 = timestamp.div_euclid(86_400);
+    let seconds: i64 = timestamp.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z: i64 = days + 719_468;
+    let era: i64 = z.div_euclid(146_097);
+    let day_of_era: i64 = z.rem_euclid(146_097);
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month: i64 = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year: i64 = year_of_era + era * 400;
+    let year: i64 = if month <= 2 { year + 1 } else { year };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+fn describe_realm(fields: Fields, address: &Pubkey, realm: &RealmV2) -> Fields {
+    fields
+        .pubkey("address", address)
+        .text("name", &realm.name)
+        .optional_pubkey("authority", realm.authority)
+        .pubkey("community_mint", &realm.community_mint)
+        .optional_pubkey("council_mint", realm.config.council_mint)
+        .amount("min_community_weight_to_create_governance", realm.config.min_community_weight_to_create_governance)
+        .debug("community_mint_max_vote_weight_source", &realm.config.community_mint_max_vote_weight_source)
+        .flag("use_community_voter_weight_addin", realm.config.use_community_voter_weight_addin)
+        .flag("use_max_community_voter_weight_addin", realm.config.use_max_community_voter_weight_addin)
+        .number("voting_proposal_count", realm.voting_proposal_count as u64)
+}
+
+fn describe_governance(fields: Fields, address: &Pubkey, governance: &GovernanceV2) -> Fields {
+    fields
+        .pubkey("address", address)
+        .debug("account_type", &governance.account_type)
+        .pubkey("realm", &governance.realm)
+        .pubkey("governed_account", &governance.governed_account)
+        .number("proposals_count", governance.proposals_count as u64)
+        .number("voting_proposal_count", governance.voting_proposal_count as u64)
+        .debug("vote_threshold_percentage", &governance.config.vote_threshold_percentage)
+        .debug("vote_tipping", &governance.config.vote_tipping)
+        .amount("min_community_weight_to_create_proposal", governance.config.min_community_weight_to_create_proposal)
+        .number("min_transaction_hold_up_time", governance.config.min_transaction_hold_up_time as u64)
+        .number("max_voting_time", governance.config.max_voting_time as u64)
+        .number("proposal_cool_off_time", governance.config.proposal_cool_off_time as u64)
+}
+
+fn describe_proposal(fields: Fields, address: &Pubkey, proposal: &ProposalV2) -> Fields {
+    let options: Vec<String> =
+        proposal.options.iter()
+            .map(|option| format!("{}: {}", option.label, fields.format_amount(option.vote_weight)) )
+            .collect();
+    fields
+        .pubkey("address", address)
+        .text("name", &proposal.name)
+        .text("description_link", &proposal.description_link)
+        .debug("state", &proposal.state)
+        .pubkey("governance", &proposal.governance)
+        .pubkey("governing_token_mint", &proposal.governing_token_mint)
+        .pubkey("token_owner_record", &proposal.token_owner_record)
+        .number("signatories_count", proposal.signatories_count as u64)
+        .number("signatories_signed_off_count", proposal.signatories_signed_off_count as u64)
+        .debug("vote_type", &proposal.vote_type)
+        .field("options", options.join(", "), Value::from(
+            proposal.options.iter()
+                .map(|option| {
+                    let mut object: Map<String, Value> = Map::new();
+                    object.insert("label".to_string(), Value::from(option.label.clone()));
+                    object.insert("vote_weight".to_string(), Value::from(option.vote_weight));
+                    object.insert("vote_result".to_string(), Value::from(format!("{:?}", option.vote_result)));
+                    Value::Object(object)
+                })
+                .collect::<Vec<Value>>()
+        ))
+        .optional_amount("deny_vote_weight", proposal.deny_vote_weight)
+        .optional_amount("max_vote_weight", proposal.max_vote_weight)
+        .timestamp("draft_at", Some(proposal.draft_at))
+        .timestamp("signing_off_at", proposal.signing_off_at)
+        .timestamp("voting_at", proposal.voting_at)
+        .timestamp("voting_completed_at", proposal.voting_completed_at)
+        .timestamp("executing_at", proposal.executing_at)
+        .timestamp("closed_at", proposal.closed_at)
+}
+
+fn describe_token_owner_record(fields: Fields, address: &Pubkey, token_owner_record: &TokenOwnerRecordV2) -> Fields {
+    fields
+        .pubkey("address", address)
+        .pubkey("realm", &token_owner_record.realm)
+        .pubkey("governing_token_mint", &token_owner_record.governing_token_mint)
+        .pubkey("governing_token_owner", &token_owner_record.governing_token_owner)
+        .optional_pubkey("governance_delegate", token_owner_record.governance_delegate)
+        .amount("governing_token_deposit_amount", token_owner_record.governing_token_deposit_amount)
+        .number("unrelinquished_votes_count", token_owner_record.unrelinquished_votes_count as u64)
+        .number("total_votes_count", token_owner_record.total_votes_count as u64)
+        .number("outstanding_proposal_count", token_owner_record.outstanding_proposal_count as u64)
+}
+
+impl Describe for Realm {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_realm(fields, &self.address, self.get_data())
+            .optional_pubkey("max_voter_weight_record", self.get_max_voter_weight_record_address())
+    }
+}
+
+impl Describe for (Pubkey, RealmV2) {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_realm(fields, &self.0, &self.1)
+    }
+}
+
+impl Describe for Governance {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_governance(fields, &self.address, self.get_data())
+    }
+}
+
+impl Describe for (Pubkey, GovernanceV2) {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_governance(fields, &self.0, &self.1)
+    }
+}
+
+impl Describe for Proposal {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_proposal(fields, &self.get_address(), &self.data)
+    }
+}
+
+impl Describe for (Pubkey, ProposalV2) {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_proposal(fields, &self.0, &self.1)
+    }
+}
+
+impl Describe for TokenOwner {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_token_owner_record(fields, &self.get_token_owner_record_address(), self.get_token_owner_record())
+            .pubkey("authority", &self.authority.pubkey())
+            .optional_pubkey("voter_weight_record", self.get_voter_weight_record_address())
+    }
+}
+
+impl Describe for (Pubkey, TokenOwnerRecordV2) {
+    fn describe(&self, fields: Fields) -> Fields {
+        describe_token_owner_record(fields, &self.0, &self.1)
+    }
+}
+
+impl Describe for (Pubkey, VoteRecordV2) {
+    fn describe(&self, fields: Fields) -> Fields {
+        fields
+            .pubkey("address", &self.0)
+            .pubkey("proposal", &self.1.proposal)
+            .pubkey("governing_token_owner", &self.1.governing_token_owner)
+            .debug("vote", &self.1.vote)
+            .amount("voter_weight", self.1.voter_weight)
+            .flag("is_relinquished", self.1.is_relinquished)
+    }
+}
+
+impl Describe for VoterWeightRecord {
+    fn describe(&self, fields: Fields) -> Fields {
+        fields
+            .pubkey("realm", &self.realm)
+            .pubkey("governing_token_mint", &self.governing_token_mint)
+            .pubkey("governing_token_owner", &self.governing_token_owner)
+            .amount("voter_weight", self.voter_weight)
+            .slot("voter_weight_expiry", self.voter_weight_expiry)
+    }
+}
+
+impl Describe for MaxVoterWeightRecord {
+    fn describe(&self, fields: Fields) -> Fields {
+        fields
+            .pubkey("realm", &self.realm)
+            .pubkey("governing_token_mint", &self.governing_token_mint)
+            .amount("max_voter_weight", self.max_voter_weight)
+            .slot("max_voter_weight_expiry", self.max_voter_weight_expiry)
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use solana_sdk::{
+    clock::{ Slot },
+    pubkey::{ Pubkey },
+    signer::{
+        Signer,
+        keypair::{ Keypair },
+    },
+};
+
+use solana_client::client_error::Result as ClientResult;
+use solana_client::client_error::ClientErrorKind;
+
+use crate::backend::ProgramTestBackend;
+use crate::commands::{ SplGovernanceInteractor, Realm, TokenOwner };
+
+/// Voter weight addin of the interactor; both are loaded into the bank
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum VoterWeightAddin {
+    /// Any weight (and weight expiry) for any voter
+    Mock,
+    /// Weights of the voter list built into the addin
+    FixedWeights,
+}
+
+/// Governance program and voter weight addins on a fresh in-process bank, with a community mint of the payer
+pub struct ProgramTestSetup {
+    pub interactor: SplGovernanceInteractor,
+    pub community_mint: Pubkey,
+    pub voter_weight_addin: Pubkey,
+}
+
+impl ProgramTestSetup {
+    pub fn new(voter_weight_addin: VoterWeightAddin) -> ClientResult<Self> {
+        let program_id: Pubkey = Pubkey::new_unique();
+        let fixed_weights_addin_id: Pubkey = Pubkey::new_unique();
+        let mock_addin_id: Pubkey = Pubkey::new_unique();
+        let voter_weight_addin: Pubkey =
+            match voter_weight_addin {
+                VoterWeightAddin::Mock => mock_addin_id,
+                VoterWeightAddin::FixedWeights => fixed_weights_addin_id,
+            };
+
+        let backend: ProgramTestBackend = ProgramTestBackend::new(ProgramTestBackend::program_test(&program_id, &fixed_weights_addin_id, &mock_addin_id));
+        let payer: Arc<dyn Signer> = Arc::new(backend.get_payer());
+        let interactor: SplGovernanceInteractor = SplGovernanceInteractor::new_with_backend(Box::new(backend), program_id, voter_weight_addin, payer.clone());
+
+        let mint_keypair: Keypair = Keypair::new();
+        interactor.create_mint(&mint_keypair, &payer.pubkey(), 0)?;
+
+        Ok(
+            ProgramTestSetup {
+                interactor,
+                community_mint: mint_keypair.pubkey(),
+                voter_weight_addin,
+            }
+        )
+    }
+}
+
+/// Realm on the mock addin with a max voter weight record and fresh voter keypairs
+pub struct ProgramTestRealm {
+    pub interactor: SplGovernanceInteractor,
+    pub realm: Realm,
+    /// One per entry of `voter_weights`; the first one is also the realm authority
+    pub voters: Vec<TokenOwner>,
+}
+
+impl ProgramTestRealm {
+    pub fn new_mock(realm_name: &str, max_voter_weight: u64, voter_weights: &[u64]) -> ClientResult<Self> {
+        if voter_weights.is_empty() {
+            return Err(ClientErrorKind::Custom("Realm needs at least one voter".to_string()).into());
+        }
+        let ProgramTestSetup { interactor, community_mint, voter_weight_addin } = ProgramTestSetup::new(VoterWeightAddin::Mock)?;
+
+        let voter_keypairs: Vec<Arc<dyn Signer>> =
+            voter_weights.iter()
+                .map(|_| Arc::new(Keypair::new()) as Arc<dyn Signer> )
+                .collect();
+
+        let max_voter_weight_record_keypair: Keypair = Keypair::new();
+        let max_voter_weight_record_pubkey: Pubkey = max_voter_weight_record_keypair.pubkey();
+        let realm: Realm = interactor.create_realm(voter_keypairs[0].clone(), &community_mint, Some(voter_weight_addin), realm_name)?;
+        interactor.setup_max_voter_weight_record_mock(&realm, max_voter_weight_record_keypair, max_voter_weight)?;
+        let realm: Realm = realm.with_max_voter_weight_record(max_voter_weight_record_pubkey);
+
+        let voters: Vec<TokenOwner> =
+            voter_keypairs.into_iter()
+                .zip(voter_weights.iter())
+                .map(|(voter, voter_weight)| create_mock_voter(&interactor, &realm, voter, *voter_weight, None) )
+                .collect::<ClientResult<Vec<TokenOwner>>>()?;
+
+        Ok(
+            ProgramTestRealm {
+                interactor,
+                realm,
+                voters,
+            }
+        )
+    }
+
+    /// Voter of the realm besides `voters`
+    pub fn create_voter(&self, voter: Arc<dyn Signer>, voter_weight: u64, voter_weight_expiry: Option<Slot>) -> ClientResult<TokenOwner> {
+        create_mock_voter(&self.interactor, &self.realm, voter, voter_weight, voter_weight_expiry)
+    }
+}
+
+/// Token owner record plus mock voter weight record, valid up to `voter_weight_expiry` slot
+pub fn create_mock_voter(interactor: &SplGovernanceInteractor, realm: &Realm, voter: Arc<dyn Signer>, voter_weight: u64, voter_weight_expiry: Option<Slot>) -> ClientResult<TokenOwner> {
+    let token_owner: TokenOwner = interactor.create_token_owner_record(realm, voter)?;
+    interactor.setup_voter_weight_record_mock(realm, token_owner, Keypair::new(), voter_weight, voter_weight_expiry)
+}
@@ -0,0 +1,341 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use solana_sdk::{
+    instruction::{ Instruction },
+    pubkey::{ Pubkey },
+};
+
+use solana_client::client_error::Result as ClientResult;
+use solana_client::client_error::ClientErrorKind;
+
+use spl_governance::state::{
+    enums::{
+        MintMaxVoteWeightSource,
+        ProposalState,
+    },
+    governance::GovernanceConfig,
+    proposal::ProposalV2,
+    proposal_transaction::{ InstructionData, ProposalTransactionV2 },
+    realm::RealmV2,
+};
+
+use crate::address_book::AddressBook;
+use crate::commands::{ SplGovernanceInteractor, Realm, Governance, Proposal, TokenOwner };
+use crate::drift::{ Drift, DriftCheck };
+use crate::manifest::AccountKind;
+use crate::scenario::GovernanceSpec;
+
+/// Desired realm config, governances and voters of an existing realm, read from a TOML file.
+/// Pubkeys can also be given as names of the address book:
+///
+/// ```toml
+/// realm_name = "Governance Test Realm"
+///
+/// [realm]
+/// voter_weight_addin = "<addin program id>"
+/// max_voter_weight_addin = "<addin program id>"
+///
+/// [[governances]]
+/// governed_account = "<pubkey>"
+/// [governances.config]
+/// yes_vote_threshold_percentage = 60
+///
+/// [[voters]]
+/// owner = "voter1"
+/// weight = 1000000
+/// ```
+#[derive(Debug,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredState {
+    pub realm_name: String,
+    pub realm: Option<RealmConfigSpec>,
+    #[serde(default)]
+    pub governances: Vec<DesiredGovernance>,
+    #[serde(default)]
+    pub voters: Vec<DesiredVoter>,
+}
+
+#[derive(Debug,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RealmConfigSpec {
+    pub voter_weight_addin: Option<String>,
+    pub max_voter_weight_addin: Option<String>,
+    #[serde(default = "default_min_community_weight_to_create_governance")]
+    pub min_community_weight_to_create_governance: u64,
+    /// `MintMaxVoteWeightSource::SupplyFraction`, 10_000_000_000 is the full supply
+    #[serde(default = "default_max_vote_weight_supply_fraction")]
+    pub max_vote_weight_supply_fraction: u64,
+}
+
+fn default_min_community_weight_to_create_governance() -> u64 { 1 }
+fn default_max_vote_weight_supply_fraction() -> u64 { MintMaxVoteWeightSource::SUPPLY_FRACTION_BASE }
+
+#[derive(Debug,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredGovernance {
+    pub governed_account: String,
+    pub config: GovernanceSpec,
+}
+
+/// Voter with a voter weight record of the fixed weights addin; the weight itself is built into the addin
+#[derive(Debug,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredVoter {
+    pub owner: String,
+    pub weight: u64,
+}
+
+impl DesiredState {
+    pub fn load(path: &Path) -> Result<Self,String> {
+        let content: String = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err) )?;
+        toml::from_str(&content).map_err(|err| format!("{}: {}", path.display(), err) )
+    }
+}
+
+/// Step of the plan reconciling the chain with the desired state
+#[derive(Debug,Clone)]
+pub enum Change {
+    /// Direct call signed by the realm authority
+    SetRealmConfig {
+        drifts: Vec<Drift>,
+        community_voter_weight_addin: Option<Pubkey>,
+        max_community_voter_weight_addin: Option<Pubkey>,
+        min_community_weight_to_create_governance: u64,
+        community_mint_max_vote_weight_source: MintMaxVoteWeightSource,
+    },
+    CreateGovernance {
+        governed_account: Pubkey,
+        config: GovernanceConfig,
+    },
+    /// Proposal in the governance itself, which has to be voted on and executed
+    SetGovernanceConfig {
+        governed_account: Pubkey,
+        drifts: Vec<Drift>,
+        config: GovernanceConfig,
+    },
+    /// `SetGovernanceConfig` proposal created by an earlier apply, not finished yet
+    PendingGovernanceConfig {
+        governed_account: Pubkey,
+        drifts: Vec<Drift>,
+        proposal: Pubkey,
+    },
+    SetupVoter {
+        owner: Pubkey,
+        create_token_owner_record: bool,
+    },
+    /// Difference this tool cannot reconcile, e.g. a weight of the fixed weights addin
+    Manual {
+        drift: Drift,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::SetRealmConfig { drifts, .. } => {
+                write!(f, "~ realm config (set_realm_config)")?;
+                for drift in drifts.iter() {
+                    write!(f, "\n    {}", drift)?;
+                }
+                Ok(())
+            },
+            Change::CreateGovernance { governed_account, config } => write!(f, "+ governance of {}: {:?}", governed_account, config),
+            Change::SetGovernanceConfig { governed_account, drifts, .. } => {
+                write!(f, "~ governance of {} config (set_governance_config proposal)", governed_account)?;
+                for drift in drifts.iter() {
+                    write!(f, "\n    {}", drift)?;
+                }
+                Ok(())
+            },
+            Change::PendingGovernanceConfig { governed_account, drifts, proposal } => {
+                write!(f, "= governance of {} config (pending in proposal {})", governed_account, proposal)?;
+                for drift in drifts.iter() {
+                    write!(f, "\n    {}", drift)?;
+                }
+                Ok(())
+            },
+            Change::SetupVoter { owner, create_token_owner_record: true } => write!(f, "+ token owner record and voter weight record of {}", owner),
+            Change::SetupVoter { owner, create_token_owner_record: false } => write!(f, "+ voter weight record of {}", owner),
+            Change::Manual { drift } => write!(f, "! {} (manual)", drift),
+        }
+    }
+}
+
+fn parse_pubkey(address_book: &AddressBook, name: &str, value: &str) -> Result<Pubkey,String> {
+    address_book.resolve(value).map_err(|err| format!("{}: {}", name, err) )
+}
+
+/// Reads the realm config, governances and voter weight records and compares them with `desired`; empty if nothing differs
+pub fn plan(interactor: &SplGovernanceInteractor, realm: &Realm, desired: &DesiredState, address_book: &AddressBook) -> Result<Vec<Change>,String> {
+    let mut changes: Vec<Change> = Vec::new();
+
+    if let Some(realm_config_spec) = &desired.realm {
+        let community_voter_weight_addin: Option<Pubkey> =
+            realm_config_spec.voter_weight_addin.as_deref().map(|addin| parse_pubkey(address_book, "voter_weight_addin", addin) ).transpose()?;
+        let max_community_voter_weight_addin: Option<Pubkey> =
+            realm_config_spec.max_voter_weight_addin.as_deref().map(|addin| parse_pubkey(address_book, "max_voter_weight_addin", addin) ).transpose()?;
+        let community_mint_max_vote_weight_source: MintMaxVoteWeightSource = MintMaxVoteWeightSource::SupplyFraction(realm_config_spec.max_vote_weight_supply_fraction);
+
+        let realm_data: RealmV2 = interactor.get_realm_v2(&realm.get_name()).map_err(|err| format!("realm: {}", err) )?;
+        let (current_voter_weight_addin, current_max_voter_weight_addin) =
+            interactor.get_realm_config(&realm.address)
+                .map(|realm_config| (realm_config.community_voter_weight_addin, realm_config.max_community_voter_weight_addin) )
+                .unwrap_or((None, None));
+
+        let drifts: Vec<Drift> =
+            DriftCheck::new(AccountKind::Realm, &realm.address)
+                .compare("community_voter_weight_addin", &community_voter_weight_addin, &current_voter_weight_addin)
+                .compare("max_community_voter_weight_addin", &max_community_voter_weight_addin, &current_max_voter_weight_addin)
+                .compare("min_community_weight_to_create_governance", &realm_config_spec.min_community_weight_to_create_governance, &realm_data.config.min_community_weight_to_create_governance)
+                .compare("community_mint_max_vote_weight_source", &community_mint_max_vote_weight_source, &realm_data.config.community_mint_max_vote_weight_source)
+                .into_drifts();
+        if !drifts.is_empty() {
+            changes.push(
+                Change::SetRealmConfig {
+                    drifts,
+                    community_voter_weight_addin,
+                    max_community_voter_weight_addin,
+                    min_community_weight_to_create_governance: realm_config_spec.min_community_weight_to_create_governance,
+                    community_mint_max_vote_weight_source,
+                }
+            );
+        }
+    }
+
+    for desired_governance in desired.governances.iter() {
+        let governed_account: Pubkey = parse_pubkey(address_book, "governed_account", &desired_governance.governed_account)?;
+        let config: GovernanceConfig = desired_governance.config.get_config();
+
+        match interactor.get_governance(realm, &governed_account).map_err(|err| format!("governance of {}: {}", governed_account, err) )? {
+            None => changes.push(Change::CreateGovernance { governed_account, config }),
+            Some(governance) => {
+                let drifts: Vec<Drift> =
+                    DriftCheck::new(AccountKind::Governance, &governance.address)
+                        .compare("config", &config, &governance.get_config())
+                        .into_drifts();
+                if drifts.is_empty() {
+                    continue;
+                }
+                match find_pending_governance_config_proposal(interactor, &governance, &config)? {
+                    Some(proposal) => changes.push(Change::PendingGovernanceConfig { governed_account, drifts, proposal }),
+                    None => changes.push(Change::SetGovernanceConfig { governed_account, drifts, config }),
+                }
+            },
+        }
+    }
+
+    for desired_voter in desired.voters.iter() {
+        let owner: Pubkey = parse_pubkey(address_book, "owner", &desired_voter.owner)?;
+        let token_owner_record_pubkey: Pubkey = interactor.get_token_owner_record_address(&owner, &realm.get_community_mint(), &realm.get_name());
+        let voter_weight_record_pubkey: Pubkey = interactor.get_voter_weight_record_address_fixed(realm, &owner);
+
+        if !interactor.account_exists(&voter_weight_record_pubkey) {
+            changes.push(
+                Change::SetupVoter {
+                    owner,
+                    create_token_owner_record: !interactor.account_exists(&token_owner_record_pubkey),
+                }
+            );
+            continue;
+        }
+
+        let voter_weight: u64 = interactor.get_voter_weight_record(&voter_weight_record_pubkey).map_err(|err| err.to_string() )?.voter_weight;
+        let drifts: Vec<Drift> =
+            DriftCheck::new(AccountKind::VoterWeightRecord, &voter_weight_record_pubkey)
+                .compare("voter_weight", &desired_voter.weight, &voter_weight)
+                .into_drifts();
+        changes.extend(drifts.into_iter().map(|drift| Change::Manual { drift } ));
+    }
+
+    Ok(changes)
+}
+
+/// Proposal of `governance` which is not finished yet and sets its config to `config`
+fn find_pending_governance_config_proposal(interactor: &SplGovernanceInteractor, governance: &Governance, config: &GovernanceConfig) -> Result<Option<Pubkey>,String> {
+    let set_governance_config: InstructionData = InstructionData::from(interactor.set_governance_config_instruction(governance, config.clone()));
+    let proposals: Vec<(Pubkey, ProposalV2)> = interactor.get_proposals(&governance.address).map_err(|err| format!("proposals of {}: {}", governance.address, err) )?;
+
+    for (proposal_pubkey, proposal) in proposals.iter() {
+        if !matches!(proposal.state, ProposalState::Draft | ProposalState::SigningOff | ProposalState::Voting | ProposalState::Succeeded | ProposalState::Executing) {
+            continue;
+        }
+        let proposal_transactions: Vec<ProposalTransactionV2> =
+            interactor.get_proposal_transactions(proposal_pubkey, proposal).map_err(|err| format!("transactions of proposal {}: {}", proposal_pubkey, err) )?;
+        if proposal_transactions.iter().any(|proposal_transaction| proposal_transaction.instructions.contains(&set_governance_config) ) {
+            return Ok(Some(*proposal_pubkey));
+        }
+    }
+    Ok(None)
+}
+
+impl Change {
+    /// Governances are created and their configs proposed by a token owner of the realm
+    fn needs_proposer(&self) -> bool {
+        matches!(self, Change::CreateGovernance { .. } | Change::SetGovernanceConfig { .. })
+    }
+}
+
+/// Issues `changes` in order: realm config and new governances directly, governance configs as proposals
+/// which are signed off and left for voting. The proposer is set up by `set_up_proposer` on the first change
+/// which needs it, not at all if none does. Returns the outcome of every change
+pub fn apply<F>(interactor: &SplGovernanceInteractor, realm: &Realm, mut set_up_proposer: F, changes: &[Change]) -> Vec<ClientResult<String>>
+where
+    F: FnMut() -> ClientResult<TokenOwner>,
+{
+    let mut proposer: Option<TokenOwner> = None;
+    let mut results: Vec<ClientResult<String>> = Vec::new();
+
+    for change in changes.iter() {
+        if change.needs_proposer() && proposer.is_none() {
+            match set_up_proposer() {
+                Ok(token_owner) => proposer = Some(token_owner),
+                Err(err) => {
+                    results.push(Err(err));
+                    continue;
+                },
+            }
+        }
+        results.push(apply_change(interactor, realm, proposer.as_ref(), change));
+    }
+    results
+}
+
+/// `proposer` is set for every change which `needs_proposer`
+fn apply_change(interactor: &SplGovernanceInteractor, realm: &Realm, proposer: Option<&TokenOwner>, change: &Change) -> ClientResult<String> {
+    match change {
+        Change::SetRealmConfig { community_voter_weight_addin, max_community_voter_weight_addin, min_community_weight_to_create_governance, community_mint_max_vote_weight_source, .. } => {
+            interactor.set_realm_config(realm, *community_voter_weight_addin, *max_community_voter_weight_addin, *min_community_weight_to_create_governance, community_mint_max_vote_weight_source.clone())
+                .map(|outcome| format!("realm config set: {}", outcome.to_string().trim_end()) )
+        },
+        Change::CreateGovernance { governed_account, config } => {
+            let proposer: &TokenOwner = proposer.expect("proposer is set up");
+            interactor.create_governance(realm, proposer, governed_account, config.clone())
+                .map(|governance| format!("governance created: {}", governance.address) )
+        },
+        Change::SetGovernanceConfig { governed_account, config, .. } => {
+            let proposer: &TokenOwner = proposer.expect("proposer is set up");
+            let governance: Governance = interactor.get_governance(realm, governed_account)?
+                .ok_or_else(|| ClientErrorKind::Custom(format!("Governance of {} not found", governed_account)) )?;
+            let set_governance_config_instruction: Instruction = interactor.set_governance_config_instruction(&governance, config.clone());
+            let proposal: Proposal = interactor.create_proposal(realm, proposer, &governance, "Set governance config", "", governance.get_proposal_count())?;
+            interactor.insert_transaction(&governance, &proposal, proposer, 0, governance.get_config().min_transaction_hold_up_time, vec![ set_governance_config_instruction ])?;
+            let proposal: Proposal = interactor.sign_off_proposal(realm, &governance, proposal, proposer)?;
+            Ok(format!("proposal {} is voting", proposal.get_address()))
+        },
+        Change::SetupVoter { owner, create_token_owner_record } => {
+            let mut instructions: Vec<Instruction> = Vec::new();
+            if *create_token_owner_record {
+                instructions.push(interactor.create_token_owner_record_instruction(realm, owner));
+            }
+            instructions.push(interactor.setup_voter_weight_record_fixed_instruction(realm, owner));
+            interactor.process_transaction(&instructions, &[])
+                .map(|outcome| format!("voter set up: {}", outcome.to_string().trim_end()) )
+        },
+        Change::PendingGovernanceConfig { proposal, .. } => Ok(format!("skipped, proposal {} is not finished yet", proposal)),
+        Change::Manual { drift } => Ok(format!("skipped, reconcile manually: {}", drift)),
+    }
+}
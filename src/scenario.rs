@@ -0,0 +1,546 @@
+use std::cell::{ RefCell, RefMut };
+use std::collections::{ HashMap, HashSet };
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{ Deserialize, Deserializer, de::Error as _ };
+
+use solana_sdk::{
+    instruction::{ Instruction },
+    pubkey::{ Pubkey },
+};
+
+use solana_client::client_error::Result as ClientResult;
+use solana_client::client_error::ClientError;
+
+use spl_governance::state::{
+    enums::{
+        ProposalState,
+        VoteThresholdPercentage,
+        VoteTipping,
+    },
+    governance::GovernanceConfig,
+};
+
+use crate::program_test::{ ProgramTestRealm };
+use crate::commands::{ SplGovernanceInteractor, Realm, Governance, Proposal, TokenOwner, yes_no_vote };
+use crate::negative::get_governance_error;
+use crate::tally::{ VoteTally, cast_vote_and_compare, finalize_vote_and_compare };
+
+const REALM_NAME: &'static str = "Scenario";
+
+/// Realm, governances, voters, proposals and the ordered actions run on them, read from a TOML file:
+///
+/// ```toml
+/// name = "majority passes"
+/// max_voter_weight = 100
+///
+/// [[voters]]
+/// name = "alice"
+/// weight = 40
+///
+/// [[governances]]
+/// name = "main"
+/// yes_vote_threshold_percentage = 60
+///
+/// [[proposals]]
+/// name = "first"
+/// governance = "main"
+/// owner = "alice"
+///
+/// [[actions]]
+/// action = "sign-off"
+/// proposal = "first"
+///
+/// [[actions]]
+/// action = "vote"
+/// proposal = "first"
+/// voter = "alice"
+/// vote = "yes"
+/// expect_state = "Voting"
+/// ```
+#[derive(Debug,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    pub max_voter_weight: u64,
+    /// The first voter is the realm authority and creates the governances
+    pub voters: Vec<VoterSpec>,
+    pub governances: Vec<GovernanceSpec>,
+    #[serde(default)]
+    pub proposals: Vec<ProposalSpec>,
+    #[serde(default)]
+    pub actions: Vec<Step>,
+}
+
+#[derive(Debug,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VoterSpec {
+    pub name: String,
+    pub weight: u64,
+}
+
+#[derive(Debug,Clone,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GovernanceSpec {
+    #[serde(default)]
+    pub name: String,
+    pub yes_vote_threshold_percentage: u8,
+    #[serde(default)]
+    pub vote_tipping: VoteTippingSpec,
+    #[serde(default = "default_max_voting_time")]
+    pub max_voting_time: u32,
+    #[serde(default)]
+    pub min_transaction_hold_up_time: u32,
+    #[serde(default = "default_min_community_weight_to_create_proposal")]
+    pub min_community_weight_to_create_proposal: u64,
+}
+
+fn default_max_voting_time() -> u32 { 3600 }
+fn default_min_community_weight_to_create_proposal() -> u64 { 1 }
+
+impl GovernanceSpec {
+    /// `YesVote` threshold config without cool-off or council
+    pub fn get_config(&self) -> GovernanceConfig {
+        GovernanceConfig {
+            vote_threshold_percentage: VoteThresholdPercentage::YesVote(self.yes_vote_threshold_percentage),
+            min_community_weight_to_create_proposal: self.min_community_weight_to_create_proposal,
+            min_transaction_hold_up_time: self.min_transaction_hold_up_time,
+            max_voting_time: self.max_voting_time,
+            vote_tipping: self.vote_tipping.into(),
+            proposal_cool_off_time: 0,
+            min_council_weight_to_create_proposal: 0,
+        }
+    }
+}
+
+#[derive(Debug,Clone,Copy,Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteTippingSpec {
+    Strict,
+    Early,
+    Disabled,
+}
+
+impl Default for VoteTippingSpec {
+    fn default() -> Self {
+        VoteTippingSpec::Strict
+    }
+}
+
+impl From<VoteTippingSpec> for VoteTipping {
+    fn from(vote_tipping: VoteTippingSpec) -> Self {
+        match vote_tipping {
+            VoteTippingSpec::Strict => VoteTipping::Strict,
+            VoteTippingSpec::Early => VoteTipping::Early,
+            VoteTippingSpec::Disabled => VoteTipping::Disabled,
+        }
+    }
+}
+
+/// Yes/no proposal created in draft state before the actions run
+#[derive(Debug,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProposalSpec {
+    pub name: String,
+    pub governance: String,
+    pub owner: String,
+    /// Config the proposal transaction sets on its governance, needed for the `execute` action
+    pub set_governance_config: Option<GovernanceSpec>,
+    /// Defaults to the `min_transaction_hold_up_time` of the governance
+    pub hold_up_time: Option<u32>,
+}
+
+#[derive(Debug,Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteSpec {
+    Yes,
+    No,
+}
+
+#[derive(Debug,Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Action {
+    SignOff { proposal: String },
+    Vote { proposal: String, voter: String, vote: VoteSpec },
+    Relinquish { proposal: String, voter: String },
+    AdvanceTime { seconds: u64 },
+    /// Advances the clock past the end of the voting time of the proposal
+    AdvancePastVotingEnd { proposal: String },
+    Finalize { proposal: String },
+    Execute { proposal: String },
+    /// Only checks `expect_state`
+    Check { proposal: String },
+}
+
+impl Action {
+    fn get_proposal(&self) -> Option<&str> {
+        match self {
+            Action::SignOff { proposal } |
+            Action::Vote { proposal, .. } |
+            Action::Relinquish { proposal, .. } |
+            Action::AdvancePastVotingEnd { proposal } |
+            Action::Finalize { proposal } |
+            Action::Execute { proposal } |
+            Action::Check { proposal } => Some(proposal),
+            Action::AdvanceTime { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::SignOff { proposal } => write!(f, "sign off {}", proposal),
+            Action::Vote { proposal, voter, vote } => write!(f, "{} votes {:?} on {}", voter, vote, proposal),
+            Action::Relinquish { proposal, voter } => write!(f, "{} relinquishes vote on {}", voter, proposal),
+            Action::AdvanceTime { seconds } => write!(f, "advance time by {}s", seconds),
+            Action::AdvancePastVotingEnd { proposal } => write!(f, "advance past voting end of {}", proposal),
+            Action::Finalize { proposal } => write!(f, "finalize {}", proposal),
+            Action::Execute { proposal } => write!(f, "execute {}", proposal),
+            Action::Check { proposal } => write!(f, "check {}", proposal),
+        }
+    }
+}
+
+/// Action with the expected outcome: without `expect_error` the action must succeed
+#[derive(Debug)]
+pub struct Step {
+    pub action: Action,
+    /// `GovernanceError` variant name, e.g. `VoteAlreadyExists`
+    pub expect_error: Option<String>,
+    /// `ProposalState` variant name of the action's proposal afterwards, e.g. `Succeeded`
+    pub expect_state: Option<String>,
+}
+
+// `#[serde(flatten)]` would ignore unknown keys: the expectations are taken out of the table
+// and the rest must be exactly the fields of the action
+impl<'de> Deserialize<'de> for Step {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut table: toml::value::Table = toml::value::Table::deserialize(deserializer)?;
+        let expect_error: Option<String> = take_string(&mut table, "expect_error").map_err(D::Error::custom)?;
+        let expect_state: Option<String> = take_string(&mut table, "expect_state").map_err(D::Error::custom)?;
+        let action: Action = toml::Value::Table(table).try_into().map_err(D::Error::custom)?;
+
+        Ok(
+            Step {
+                action,
+                expect_error,
+                expect_state,
+            }
+        )
+    }
+}
+
+fn take_string(table: &mut toml::value::Table, key: &str) -> Result<Option<String>,String> {
+    match table.remove(key) {
+        Some(toml::Value::String(value)) => Ok(Some(value)),
+        Some(value) => Err(format!("{} must be a string, not {}", key, value.type_str())),
+        None => Ok(None),
+    }
+}
+
+pub struct StepResult {
+    pub description: String,
+    /// `Err` describes how the outcome differs from the expectation
+    pub result: Result<(),String>,
+}
+
+pub struct ScenarioReport {
+    pub name: String,
+    /// `Err` if the realm, governances or proposals could not be set up
+    pub setup: Result<(),String>,
+    pub steps: Vec<StepResult>,
+}
+
+impl ScenarioReport {
+    pub fn is_passed(&self) -> bool {
+        self.setup.is_ok() && self.steps.iter().all(|step| step.result.is_ok() )
+    }
+}
+
+impl fmt::Display for ScenarioReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Scenario {}: {}", self.name, if self.is_passed() { "PASS" } else { "FAIL" })?;
+        if let Err(err) = &self.setup {
+            writeln!(f, "  FAIL setup: {}", err)?;
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            match &step.result {
+                Ok(()) => writeln!(f, "  PASS {:>3} {}", index + 1, step.description)?,
+                Err(err) => writeln!(f, "  FAIL {:>3} {}: {}", index + 1, step.description, err)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Scenario entities by name
+struct ScenarioState<'a> {
+    interactor: SplGovernanceInteractor,
+    realm: Realm,
+    voters: HashMap<&'a str, TokenOwner>,
+    governances: HashMap<&'a str, Governance>,
+    /// Proposal, its governance name and the address of its proposal transaction
+    proposals: HashMap<&'a str, (Proposal, &'a str, Option<Pubkey>)>,
+    /// Expected votes of each proposal voted on, compared with the proposal after every vote
+    tallies: RefCell<HashMap<String, VoteTally>>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self,String> {
+        let content: String = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err) )?;
+        toml::from_str(&content).map_err(|err| format!("{}: {}", path.display(), err) )
+    }
+
+    /// Sets up the scenario on a fresh in-process bank and runs all actions, a failed step does not stop the run
+    pub fn run(&self) -> ScenarioReport {
+        let mut report: ScenarioReport =
+            ScenarioReport {
+                name: self.name.clone(),
+                setup: Ok(()),
+                steps: Vec::new(),
+            };
+
+        let state: ScenarioState =
+            match self.setup() {
+                Ok(state) => state,
+                Err(err) => {
+                    report.setup = Err(err);
+                    return report;
+                },
+            };
+
+        for step in self.actions.iter() {
+            report.steps.push(
+                StepResult {
+                    description: step.action.to_string(),
+                    result: self.run_step(&state, step),
+                }
+            );
+        }
+        report
+    }
+
+    /// Names must be unique and non-empty among voters, governances and proposals each
+    fn validate_names(&self) -> Result<(),String> {
+        check_names("voter", self.voters.iter().map(|voter| voter.name.as_str() ))?;
+        check_names("governance", self.governances.iter().map(|governance| governance.name.as_str() ))?;
+        check_names("proposal", self.proposals.iter().map(|proposal| proposal.name.as_str() ))
+    }
+
+    /// Realm on the mock addin with `max_voter_weight` and a fresh keypair per voter; the first voter is the realm authority
+    fn create_realm(&self) -> Result<(SplGovernanceInteractor, Realm, Vec<TokenOwner>),String> {
+        let voter_weights: Vec<u64> = self.voters.iter().map(|voter_spec| voter_spec.weight ).collect();
+        let ProgramTestRealm { interactor, realm, voters } = ProgramTestRealm::new_mock(REALM_NAME, self.max_voter_weight, &voter_weights)
+            .map_err(|err| format!("realm: {}", err) )?;
+
+        Ok((interactor, realm, voters))
+    }
+
+    fn setup(&self) -> Result<ScenarioState,String> {
+        if self.voters.is_empty() {
+            return Err("no voters".to_string());
+        }
+        self.validate_names()?;
+        let (interactor, realm, token_owners) = self.create_realm()?;
+
+        let mut governances: HashMap<&str, Governance> = HashMap::new();
+        for governance_spec in self.governances.iter() {
+            // Every governance governs a new (unique) account and is created by the first voter
+            let governance: Governance = interactor.create_governance(&realm, &token_owners[0], &Pubkey::new_unique(), governance_spec.get_config())
+                .map_err(|err| format!("governance {}: {}", governance_spec.name, err) )?;
+            governances.insert(governance_spec.name.as_str(), governance);
+        }
+
+        let mut state: ScenarioState =
+            ScenarioState {
+                interactor,
+                realm,
+                voters: self.voters.iter().map(|voter| voter.name.as_str() ).zip(token_owners.into_iter()).collect(),
+                governances,
+                proposals: HashMap::new(),
+                tallies: RefCell::new(HashMap::new()),
+            };
+
+        let mut proposal_counts: HashMap<&str, u32> = HashMap::new();
+        for proposal_spec in self.proposals.iter() {
+            let governance_spec: &GovernanceSpec =
+                self.governances.iter()
+                    .find(|governance| governance.name == proposal_spec.governance )
+                    .ok_or_else(|| format!("proposal {}: unknown governance {}", proposal_spec.name, proposal_spec.governance))?;
+            let governance: &Governance = &state.governances[governance_spec.name.as_str()];
+            let owner: &TokenOwner = state.get_voter(&proposal_spec.owner)?;
+
+            let proposal_index: &mut u32 = proposal_counts.entry(governance_spec.name.as_str()).or_insert(0);
+            let proposal: Proposal =
+                state.interactor.create_proposal(&state.realm, owner, governance, &proposal_spec.name, "", *proposal_index)
+                    .map_err(|err| format!("proposal {}: {}", proposal_spec.name, err) )?;
+            *proposal_index += 1;
+
+            let proposal_transaction_address: Option<Pubkey> =
+                match &proposal_spec.set_governance_config {
+                    Some(new_governance_spec) => {
+                        let set_governance_config_instruction: Instruction = state.interactor.set_governance_config_instruction(governance, new_governance_spec.get_config());
+                        let hold_up_time: u32 = proposal_spec.hold_up_time.unwrap_or(governance_spec.min_transaction_hold_up_time);
+                        let address: Pubkey =
+                            state.interactor.insert_transaction(governance, &proposal, owner, 0, hold_up_time, vec![ set_governance_config_instruction ])
+                                .map_err(|err| format!("proposal {} transaction: {}", proposal_spec.name, err) )?;
+                        Some(address)
+                    },
+                    None => None,
+                };
+
+            state.proposals.insert(proposal_spec.name.as_str(), (proposal, governance_spec.name.as_str(), proposal_transaction_address));
+        }
+
+        Ok(state)
+    }
+
+    fn run_step(&self, state: &ScenarioState, step: &Step) -> Result<(),String> {
+        let result: ClientResult<()> = state.run_action(&step.action, self)?;
+
+        match (result, &step.expect_error) {
+            (Ok(()), None) => {},
+            (Ok(()), Some(expected_error)) => return Err(format!("succeeded instead of failing with {}", expected_error)),
+            (Err(error), None) => return Err(format!("failed with {}", describe_error(&error))),
+            (Err(error), Some(expected_error)) => {
+                let actual_error: String = describe_error(&error);
+                if actual_error != *expected_error {
+                    return Err(format!("failed with {} instead of {}", actual_error, expected_error));
+                }
+            },
+        }
+
+        if let Some(expected_state) = &step.expect_state {
+            let proposal_name: &str = step.action.get_proposal().ok_or("expect_state needs an action on a proposal")?;
+            let (proposal, _, _) = state.get_proposal(proposal_name)?;
+            let actual_state: ProposalState = state.interactor.reload_proposal(proposal).map_err(|err| format!("proposal {}: {}", proposal_name, err) )?.data.state;
+            if format!("{:?}", actual_state) != *expected_state {
+                return Err(format!("proposal {} is {:?} instead of {}", proposal_name, actual_state, expected_state));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ScenarioState<'a> {
+    fn get_voter(&self, name: &str) -> Result<&TokenOwner,String> {
+        self.voters.get(name)
+            .ok_or_else(|| format!("unknown voter {}", name))
+    }
+
+    fn get_proposal(&self, name: &str) -> Result<&(Proposal, &'a str, Option<Pubkey>),String> {
+        self.proposals.get(name).ok_or_else(|| format!("unknown proposal {}", name))
+    }
+
+    /// Outer `Err` for invalid scenarios (unknown names) and votes the tally does not predict, inner for the failed transaction
+    fn run_action(&self, action: &Action, scenario: &Scenario) -> Result<ClientResult<()>,String> {
+        let interactor = &self.interactor;
+        let realm = &self.realm;
+
+        if let Action::AdvanceTime { seconds } = action {
+            return Ok(interactor.advance_clock(*seconds).map(|_| () ));
+        }
+
+        let proposal_name: &str = action.get_proposal().unwrap();
+        let (proposal, governance_name, proposal_transaction_address) = self.get_proposal(proposal_name)?;
+        let governance: &Governance = &self.governances[*governance_name];
+        // Votes and transactions are checked against the current state of the proposal
+        let proposal: Proposal =
+            match interactor.reload_proposal(proposal) {
+                Ok(proposal) => proposal,
+                Err(err) => return Ok(Err(err)),
+            };
+
+        let result: ClientResult<()> =
+            match action {
+                Action::SignOff { .. } => {
+                    let owner: &TokenOwner = self.get_proposal_owner(scenario, proposal_name)?;
+                    interactor.sign_off_proposal(realm, governance, proposal, owner).map(|_| () )
+                },
+                Action::Vote { voter, vote, .. } => {
+                    let voter: &TokenOwner = self.get_voter(voter)?;
+                    let mut tallies: RefMut<HashMap<String, VoteTally>> = self.tallies.borrow_mut();
+                    let tally: &mut VoteTally = self.get_tally(&mut tallies, proposal_name, governance, &proposal)?;
+                    match cast_vote_and_compare(interactor, tally, realm, governance, &proposal, voter, yes_no_vote(matches!(vote, VoteSpec::Yes))) {
+                        Ok(mismatches) => check_mismatches(&mismatches)?,
+                        Err(err) => return Ok(Err(err)),
+                    }
+                    Ok(())
+                },
+                Action::Relinquish { voter, .. } => {
+                    let voter: &TokenOwner = self.get_voter(voter)?;
+                    let result: ClientResult<()> = interactor.relinquish_vote(realm, governance, &proposal, voter).map(|_| () );
+                    // The tally does not model withdrawn votes: the next one starts from the proposal again
+                    self.tallies.borrow_mut().remove(proposal_name);
+                    result
+                },
+                Action::AdvancePastVotingEnd { .. } => {
+                    interactor.advance_past_voting_end(governance, &proposal).map(|_| () )
+                },
+                Action::Finalize { .. } => {
+                    let mut tallies: RefMut<HashMap<String, VoteTally>> = self.tallies.borrow_mut();
+                    let tally: &mut VoteTally = self.get_tally(&mut tallies, proposal_name, governance, &proposal)?;
+                    match finalize_vote_and_compare(interactor, tally, realm, governance, &proposal) {
+                        Ok(mismatches) => check_mismatches(&mismatches)?,
+                        Err(err) => return Ok(Err(err)),
+                    }
+                    Ok(())
+                },
+                Action::Execute { .. } => {
+                    let proposal_transaction_address: &Pubkey =
+                        proposal_transaction_address.as_ref().ok_or_else(|| format!("proposal {} has no transaction", proposal_name))?;
+                    interactor.execute_transaction(governance, &proposal, proposal_transaction_address).map(|_| () )
+                },
+                Action::Check { .. } => Ok(()),
+                Action::AdvanceTime { .. } => unreachable!(),
+            };
+        Ok(result)
+    }
+
+    /// Tally of the proposal, started from its current votes on first use
+    fn get_tally<'t>(&self, tallies: &'t mut HashMap<String, VoteTally>, proposal_name: &str, governance: &Governance, proposal: &Proposal) -> Result<&'t mut VoteTally,String> {
+        if !tallies.contains_key(proposal_name) {
+            let max_vote_weight: u64 = self.interactor.get_max_vote_weight(&self.realm).map_err(|err| format!("max vote weight: {}", err) )?;
+            let tally: VoteTally = VoteTally::new(&governance.get_config(), max_vote_weight, &proposal.data)?;
+            tallies.insert(proposal_name.to_string(), tally);
+        }
+        Ok(tallies.get_mut(proposal_name).unwrap())
+    }
+
+    fn get_proposal_owner(&self, scenario: &Scenario, proposal_name: &str) -> Result<&TokenOwner,String> {
+        let proposal_spec: &ProposalSpec = scenario.proposals.iter().find(|proposal| proposal.name == proposal_name ).unwrap();
+        self.get_voter(&proposal_spec.owner)
+    }
+}
+
+/// `GovernanceError` variant name of a governance program error, the client error otherwise
+fn describe_error(error: &ClientError) -> String {
+    match get_governance_error(error) {
+        Some(governance_error) => format!("{:?}", governance_error),
+        None => error.to_string(),
+    }
+}
+
+fn check_mismatches(mismatches: &[String]) -> Result<(),String> {
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("proposal differs from the tally: {}", mismatches.join("; ")))
+    }
+}
+
+fn check_names<'a>(kind: &str, names: impl Iterator<Item = &'a str>) -> Result<(),String> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    for name in names {
+        if name.is_empty() {
+            return Err(format!("{} without a name", kind));
+        }
+        if !seen.insert(name) {
+            return Err(format!("duplicate {} {}", kind, name));
+        }
+    }
+    Ok(())
+}
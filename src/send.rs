@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    transaction::TransactionError,
+};
+
+use solana_client::client_error::{ ClientError, ClientErrorKind };
+use solana_client::rpc_request::RpcError;
+
+/// How the interactor sends transactions in `ExecutionMode::Send`
+#[derive(Debug,Clone)]
+pub struct SendStrategy {
+    /// Additional attempts after a retryable failure
+    pub retries: u32,
+    /// Delay before the first retry, doubled on every further one
+    pub backoff: Duration,
+    pub skip_preflight: bool,
+    /// Commitment the transaction is confirmed with (and preflight is run at)
+    pub commitment: CommitmentConfig,
+    /// Interval of signature status polling while waiting for the confirmation
+    pub poll_interval: Duration,
+    /// Longest wait for the confirmation of a durable nonce transaction, which does not expire with a blockhash
+    pub confirmation_timeout: Duration,
+    /// Called before every retry with the failure and the retry number, e.g. to log it
+    pub on_retry: Option<fn(&ClientError, u32)>,
+}
+
+impl Default for SendStrategy {
+    fn default() -> Self {
+        SendStrategy {
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            skip_preflight: false,
+            commitment: CommitmentConfig::confirmed(),
+            poll_interval: Duration::from_millis(500),
+            confirmation_timeout: Duration::from_secs(120),
+            on_retry: None,
+        }
+    }
+}
+
+impl SendStrategy {
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn get_backoff(&self, attempt: u32) -> Duration {
+        self.backoff * 2u32.saturating_pow(attempt.saturating_sub(1))
+    }
+}
+
+/// Failures worth another attempt with a fresh blockhash: the blockhash expired (or is unknown) before
+/// the transaction landed. Transport errors are not, the transaction may still land: the backend
+/// polls and resends the same signed transaction instead. Program errors are final
+pub fn is_retryable(error: &ClientError) -> bool {
+    matches!(error.get_transaction_error(), Some(TransactionError::BlockhashNotFound))
+}
+
+/// Request failed on the way to or from the node (connection, timeout, malformed response) rather than by the transaction.
+/// Error responses of the node (e.g. a failed preflight) are final
+pub fn is_transport_error(error: &ClientError) -> bool {
+    error.get_transaction_error().is_none() &&
+        matches!(
+            error.kind(),
+            ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) | ClientErrorKind::RpcError(RpcError::RpcRequestError(_))
+        )
+}
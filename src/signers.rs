@@ -0,0 +1,106 @@
+use std::{
+    error::Error,
+    io::Write,
+    process::{ Command, Stdio },
+    str::FromStr,
+    sync::Arc,
+};
+
+use solana_sdk::{
+    pubkey::{ Pubkey },
+    signature::{ Signature },
+    signer::{
+        Signer,
+        SignerError,
+        keypair::{ read_keypair, read_keypair_file },
+        null_signer::{ NullSigner },
+        presigner::{ Presigner },
+    },
+};
+
+/// Resolves a signer description into a shared signer handle:
+///
+/// * `env:VAR` - keypair JSON bytes stored in the environment variable `VAR`
+/// * `pubkey:PUBKEY` - pubkey only, signs nothing (offline flows)
+/// * `presigned:PUBKEY=SIGNATURE` - signature collected elsewhere for a known message
+/// * `exec:PUBKEY:COMMAND` - external signer process (see `ExternalSigner`)
+/// * anything else - keypair file path
+pub fn signer_from_path(path: &str) -> Result<Arc<dyn Signer>,Box<dyn Error>> {
+    if let Some(var) = path.strip_prefix("env:") {
+        let keypair_json: String = std::env::var(var)?;
+        let keypair = read_keypair(&mut keypair_json.as_bytes())?;
+        Ok(Arc::new(keypair))
+    } else if let Some(pubkey) = path.strip_prefix("pubkey:") {
+        let pubkey: Pubkey = Pubkey::from_str(pubkey)?;
+        Ok(Arc::new(NullSigner::new(&pubkey)))
+    } else if let Some(presigned) = path.strip_prefix("presigned:") {
+        let (pubkey, signature) = presigned.split_once('=').ok_or("Expected presigned:PUBKEY=SIGNATURE")?;
+        let pubkey: Pubkey = Pubkey::from_str(pubkey)?;
+        let signature: Signature = Signature::from_str(signature)?;
+        Ok(Arc::new(Presigner::new(&pubkey, &signature)))
+    } else if let Some(external) = path.strip_prefix("exec:") {
+        let (pubkey, command) = external.split_once(':').ok_or("Expected exec:PUBKEY:COMMAND")?;
+        let pubkey: Pubkey = Pubkey::from_str(pubkey)?;
+        Ok(Arc::new(ExternalSigner::new(pubkey, command)))
+    } else {
+        let keypair = read_keypair_file(path)?;
+        Ok(Arc::new(keypair))
+    }
+}
+
+/// Signer delegating to an external process (hardware wallet bridge, remote KMS, ...).
+/// The command gets the serialized message base64-encoded on stdin
+/// and must print the base58 signature on stdout
+pub struct ExternalSigner {
+    pubkey: Pubkey,
+    command: String,
+}
+
+impl ExternalSigner {
+    pub fn new(pubkey: Pubkey, command: &str) -> Self {
+        ExternalSigner {
+            pubkey,
+            command: command.to_string(),
+        }
+    }
+}
+
+impl Signer for ExternalSigner {
+    fn try_pubkey(&self) -> Result<Pubkey,SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature,SignerError> {
+        let mut child =
+            Command::new("sh")
+                .arg("-c")
+                .arg(&self.command)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|e| SignerError::Connection(e.to_string()) )?;
+
+        child.stdin.take().unwrap()
+            .write_all(base64::encode(message).as_bytes())
+            .map_err(|e| SignerError::Connection(e.to_string()) )?;
+
+        let output = child.wait_with_output().map_err(|e| SignerError::Connection(e.to_string()) )?;
+        if !output.status.success() {
+            return Err(SignerError::Custom(format!("External signer exited with {}", output.status)));
+        }
+
+        let signature: Signature =
+            Signature::from_str(String::from_utf8_lossy(&output.stdout).trim())
+                .map_err(|e| SignerError::Custom(e.to_string()) )?;
+
+        if signature.verify(self.pubkey.as_ref(), message) {
+            Ok(signature)
+        } else {
+            Err(SignerError::Custom("External signer returned an invalid signature".to_string()))
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
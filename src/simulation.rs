@@ -0,0 +1,75 @@
+use std::fmt;
+
+use num_traits::FromPrimitive;
+
+use solana_sdk::{
+    pubkey::{ Pubkey },
+    instruction::{ InstructionError },
+    transaction::{ Transaction, TransactionError },
+};
+
+use solana_client::rpc_response::{ RpcSimulateTransactionResult };
+
+use spl_governance::error::{ GovernanceError };
+
+/// Outcome of a transaction simulated instead of sent (dry-run mode)
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    pub error: Option<TransactionError>,
+    /// `error` decoded when it was raised by the governance program
+    pub governance_error: Option<GovernanceError>,
+}
+
+impl SimulationReport {
+    pub fn new(transaction: &Transaction, governance_program_id: &Pubkey, result: RpcSimulateTransactionResult) -> Self {
+        let governance_error: Option<GovernanceError> =
+            result.err.as_ref()
+                .and_then(|err| decode_governance_error(transaction, governance_program_id, err) );
+
+        SimulationReport {
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            error: result.err,
+            governance_error,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.error, &self.governance_error) {
+            (None, _) => writeln!(f, "Simulation succeeded")?,
+            (Some(err), None) => writeln!(f, "Simulation failed: {}", err)?,
+            (Some(err), Some(governance_error)) => writeln!(f, "Simulation failed: {} ({:?}: {})", err, governance_error, governance_error)?,
+        }
+        if let Some(units_consumed) = self.units_consumed {
+            writeln!(f, "Compute units consumed: {}", units_consumed)?;
+        }
+        for log in self.logs.iter() {
+            writeln!(f, "  {}", log)?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps a custom instruction error back to `GovernanceError` if the failed instruction targets the governance program
+pub fn decode_governance_error(transaction: &Transaction, governance_program_id: &Pubkey, err: &TransactionError) -> Option<GovernanceError> {
+    match err {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            let instruction = transaction.message.instructions.get(*index as usize)?;
+            let program_id: &Pubkey = transaction.message.account_keys.get(instruction.program_id_index as usize)?;
+            if program_id == governance_program_id {
+                GovernanceError::from_u32(*code)
+            } else {
+                None
+            }
+        },
+        _ => None,
+    }
+}
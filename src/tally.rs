@@ -0,0 +1,236 @@
+use solana_client::client_error::Result as ClientResult;
+use solana_client::client_error::ClientErrorKind;
+
+use spl_governance::state::{
+    enums::{
+        ProposalState,
+        VoteThresholdPercentage,
+        VoteTipping,
+    },
+    governance::GovernanceConfig,
+    proposal::{ ProposalV2, VoteType },
+    vote_record::{ Vote, VoteChoice, VoteRecordV2 },
+};
+
+use crate::commands::{ SplGovernanceInteractor, Outcome, Realm, Governance, Proposal, TokenOwner };
+
+/// Offline model of the spl-governance 2.2 vote counting: predicts the proposal state
+/// after every vote (tipping) and at finalization from the voter weights alone
+#[derive(Debug,Clone)]
+pub struct VoteTally {
+    yes_vote_threshold_percentage: u8,
+    vote_tipping: VoteTipping,
+    max_vote_weight: u64,
+    vote_type: VoteType,
+    option_vote_weights: Vec<u64>,
+    /// `None` for proposals without the deny option
+    deny_vote_weight: Option<u64>,
+    state: ProposalState,
+}
+
+impl VoteTally {
+    /// Tally continuing from the current votes and state of `proposal`. Only `YesVote` thresholds
+    /// are modelled, the program rejects `Quorum` ones as well
+    pub fn new(gov_config: &GovernanceConfig, max_vote_weight: u64, proposal: &ProposalV2) -> Result<Self,String> {
+        let yes_vote_threshold_percentage: u8 =
+            match gov_config.vote_threshold_percentage {
+                VoteThresholdPercentage::YesVote(percentage) => percentage,
+                VoteThresholdPercentage::Quorum(percentage) => return Err(format!("Quorum({}) vote threshold is not supported", percentage)),
+            };
+
+        Ok(VoteTally {
+            yes_vote_threshold_percentage,
+            vote_tipping: gov_config.vote_tipping.clone(),
+            max_vote_weight,
+            vote_type: proposal.vote_type.clone(),
+            option_vote_weights: proposal.options.iter().map(|option| option.vote_weight ).collect(),
+            deny_vote_weight: proposal.deny_vote_weight,
+            state: proposal.state.clone(),
+        })
+    }
+
+    pub fn get_state(&self) -> ProposalState {
+        self.state.clone()
+    }
+
+    /// Max vote weight the program counts with: the one of the record, raised to the total weight
+    /// of the votes cast if they exceed it
+    pub fn get_max_vote_weight(&self) -> u64 {
+        let total_vote_weight: u64 =
+            self.option_vote_weights.iter()
+                .fold(self.deny_vote_weight.unwrap_or(0), |total, option_vote_weight| total.saturating_add(*option_vote_weight) );
+        self.max_vote_weight.max(total_vote_weight)
+    }
+
+    /// Smallest weight that reaches the threshold: the threshold percentage of the max vote weight, rounded up
+    pub fn get_min_vote_threshold_weight(&self) -> u64 {
+        let numerator: u128 = self.yes_vote_threshold_percentage as u128 * self.get_max_vote_weight() as u128;
+        ((numerator + 99) / 100) as u64
+    }
+
+    /// Adds a vote and applies tipping. `Err` for votes the program would reject: voting is over,
+    /// a deny vote without the deny option, choices not matching the options, percentages other
+    /// than 0 and 100 or more than one 100% choice on a single choice proposal
+    pub fn cast_vote(&mut self, voter_weight: u64, vote: &Vote) -> Result<ProposalState,String> {
+        if self.state != ProposalState::Voting {
+            return Err(format!("Proposal is {:?}, not voting", self.state));
+        }
+
+        match vote {
+            Vote::Approve(choices) => {
+                if choices.len() != self.option_vote_weights.len() {
+                    return Err(format!("Vote has {} choices, the proposal {} options", choices.len(), self.option_vote_weights.len()));
+                }
+                if let Some(choice) = choices.iter().find(|choice| choice.weight_percentage != 0 && choice.weight_percentage != 100 ) {
+                    return Err(format!("Choice weight percentage {} is neither 0 nor 100", choice.weight_percentage));
+                }
+                let full_choices_count: usize = choices.iter().filter(|choice| choice.weight_percentage == 100 ).count();
+                if self.vote_type == VoteType::SingleChoice && full_choices_count > 1 {
+                    return Err(format!("Single choice vote with {} choices at 100%", full_choices_count));
+                }
+                for (option_vote_weight, choice) in self.option_vote_weights.iter_mut().zip(choices.iter()) {
+                    *option_vote_weight += get_choice_weight(voter_weight, choice);
+                }
+            },
+            Vote::Deny => {
+                let deny_vote_weight: u64 = self.deny_vote_weight.ok_or("Deny vote on a proposal without the deny option")?;
+                self.deny_vote_weight = Some(deny_vote_weight + voter_weight);
+            },
+        }
+
+        if let Some(tipped_state) = self.get_tipped_state() {
+            self.state = tipped_state;
+        }
+        Ok(self.get_state())
+    }
+
+    /// Only single choice proposals with one option and the deny option tip
+    fn get_tipped_state(&self) -> Option<ProposalState> {
+        if self.vote_tipping == VoteTipping::Disabled || self.vote_type != VoteType::SingleChoice || self.option_vote_weights.len() != 1 {
+            return None;
+        }
+        let yes_vote_weight: u64 = self.option_vote_weights[0];
+        let deny_vote_weight: u64 = self.deny_vote_weight?;
+        let max_vote_weight: u64 = self.get_max_vote_weight();
+        let min_vote_threshold_weight: u64 = self.get_min_vote_threshold_weight();
+
+        if yes_vote_weight >= min_vote_threshold_weight && yes_vote_weight > max_vote_weight.saturating_sub(yes_vote_weight) {
+            Some(ProposalState::Succeeded)
+        } else if self.vote_tipping == VoteTipping::Early && yes_vote_weight >= min_vote_threshold_weight && yes_vote_weight > deny_vote_weight {
+            Some(ProposalState::Succeeded)
+        } else if deny_vote_weight > max_vote_weight.saturating_sub(min_vote_threshold_weight)
+            || deny_vote_weight >= max_vote_weight.saturating_sub(deny_vote_weight)
+        {
+            Some(ProposalState::Defeated)
+        } else {
+            None
+        }
+    }
+
+    /// State after `finalize_vote`: an option succeeds if it reaches the threshold and beats the deny weight,
+    /// a single choice proposal fails on a tie between succeeded options
+    pub fn finalize(&mut self) -> ProposalState {
+        if self.state != ProposalState::Voting {
+            return self.get_state();
+        }
+
+        let min_vote_threshold_weight: u64 = self.get_min_vote_threshold_weight();
+        let deny_vote_weight: u64 = self.deny_vote_weight.unwrap_or(0);
+
+        let succeeded_option_weights: Vec<u64> =
+            self.option_vote_weights.iter()
+                .filter(|option_vote_weight| **option_vote_weight >= min_vote_threshold_weight && **option_vote_weight > deny_vote_weight )
+                .cloned()
+                .collect();
+        let best_option_weight: Option<u64> = succeeded_option_weights.iter().max().cloned();
+        let best_options_count: usize = succeeded_option_weights.iter().filter(|weight| Some(**weight) == best_option_weight ).count();
+
+        self.state =
+            match (best_option_weight, &self.vote_type) {
+                (None, _) => ProposalState::Defeated,
+                (Some(_), VoteType::SingleChoice) if best_options_count > 1 => ProposalState::Defeated,
+                (Some(_), _) => ProposalState::Succeeded,
+            };
+        self.get_state()
+    }
+
+    /// Differences between the on-chain proposal and the model, empty if they agree
+    pub fn get_mismatches(&self, proposal: &ProposalV2) -> Vec<String> {
+        let mut mismatches: Vec<String> = Vec::new();
+
+        if proposal.state != self.state {
+            mismatches.push(format!("state: on-chain {:?}, model {:?}", proposal.state, self.state));
+        }
+        let option_vote_weights: Vec<u64> = proposal.options.iter().map(|option| option.vote_weight ).collect();
+        if option_vote_weights != self.option_vote_weights {
+            mismatches.push(format!("option vote weights: on-chain {:?}, model {:?}", option_vote_weights, self.option_vote_weights));
+        }
+        if proposal.deny_vote_weight != self.deny_vote_weight {
+            mismatches.push(format!("deny vote weight: on-chain {:?}, model {:?}", proposal.deny_vote_weight, self.deny_vote_weight));
+        }
+
+        mismatches
+    }
+}
+
+/// Casts `vote` on-chain, applies it to `tally` with the voter weight from its record and
+/// returns the differences between the reloaded proposal and the model. A simulated or exported
+/// vote leaves the proposal unchanged, so there is nothing to compare
+pub fn cast_vote_and_compare(interactor: &SplGovernanceInteractor, tally: &mut VoteTally, realm: &Realm, governance: &Governance, proposal: &Proposal, voter: &TokenOwner, vote: Vote) -> ClientResult<Vec<String>> {
+    let voter_weight: u64 = interactor.get_voter_weight(voter)?;
+    let outcome: Outcome = interactor.cast_vote_with(realm, governance, proposal, voter, vote.clone())?;
+    tally.cast_vote(voter_weight, &vote)
+        .map_err(|err| ClientErrorKind::Custom(format!("Vote accepted on-chain but not by the model: {}", err)) )?;
+    get_sent_mismatches(interactor, tally, proposal, &outcome)
+}
+
+/// Finalizes the vote on-chain and in `tally`, returns the differences as `cast_vote_and_compare`
+pub fn finalize_vote_and_compare(interactor: &SplGovernanceInteractor, tally: &mut VoteTally, realm: &Realm, governance: &Governance, proposal: &Proposal) -> ClientResult<Vec<String>> {
+    let outcome: Outcome = interactor.finalize_vote(realm, governance, proposal)?;
+    tally.finalize();
+    get_sent_mismatches(interactor, tally, proposal, &outcome)
+}
+
+fn get_sent_mismatches(interactor: &SplGovernanceInteractor, tally: &VoteTally, proposal: &Proposal, outcome: &Outcome) -> ClientResult<Vec<String>> {
+    match outcome {
+        Outcome::Sent(_) => Ok(tally.get_mismatches(&interactor.reload_proposal(proposal)?.data)),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Differences between the option and deny vote weights of `proposal` and the sum of its vote records.
+/// Relinquished records still count: they are only kept once the voting is over, when the weights stay
+pub fn get_vote_record_mismatches(proposal: &ProposalV2, vote_records: &[VoteRecordV2]) -> Vec<String> {
+    let mut option_vote_weights: Vec<u64> = vec![ 0; proposal.options.len() ];
+    let mut deny_vote_weight: u64 = 0;
+
+    for vote_record in vote_records.iter() {
+        match &vote_record.vote {
+            Vote::Approve(choices) => {
+                for (option_vote_weight, choice) in option_vote_weights.iter_mut().zip(choices.iter()) {
+                    *option_vote_weight += get_choice_weight(vote_record.voter_weight, choice);
+                }
+            },
+            Vote::Deny => deny_vote_weight += vote_record.voter_weight,
+        }
+    }
+
+    let mut mismatches: Vec<String> = Vec::new();
+    let proposal_option_vote_weights: Vec<u64> = proposal.options.iter().map(|option| option.vote_weight ).collect();
+    if proposal_option_vote_weights != option_vote_weights {
+        mismatches.push(format!("option vote weights: proposal {:?}, vote records {:?}", proposal_option_vote_weights, option_vote_weights));
+    }
+    if proposal.deny_vote_weight.unwrap_or(0) != deny_vote_weight {
+        mismatches.push(format!("deny vote weight: proposal {:?}, vote records {}", proposal.deny_vote_weight, deny_vote_weight));
+    }
+    mismatches
+}
+
+/// Weight a choice adds to its option: all of the voter weight at 100%, none at 0% (the only percentages the program accepts)
+fn get_choice_weight(voter_weight: u64, choice: &VoteChoice) -> u64 {
+    if choice.weight_percentage == 100 {
+        voter_weight
+    } else {
+        0
+    }
+}
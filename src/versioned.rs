@@ -0,0 +1,186 @@
+use std::fmt;
+
+use borsh::BorshDeserialize;
+
+use solana_sdk::{
+    account::{ Account },
+    account_info::{ AccountInfo, IntoAccountInfo },
+    program_error::ProgramError,
+    pubkey::{ Pubkey },
+};
+
+use spl_governance::state::{
+    enums::GovernanceAccountType,
+    governance::{ GovernanceV2, get_governance_data },
+    proposal::{ ProposalV2, get_proposal_data },
+    realm::{ RealmV2, get_realm_data },
+    token_owner_record::{ TokenOwnerRecordV2, get_token_owner_record_data },
+    vote_record::{ VoteRecordV2, get_vote_record_data },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountVersion {
+    V1,
+    V2,
+}
+
+/// Account decoded from either layout; V1 accounts are translated to the V2 structure by the program itself
+#[derive(Debug, Clone)]
+pub struct Versioned<T> {
+    pub version: AccountVersion,
+    pub data: T,
+}
+
+/// Governance account with V1 and V2 layouts, decoded through the program's own getter
+pub trait VersionedAccount: Sized {
+    const NAME: &'static str;
+
+    /// Account types of this account with the layout version they use
+    fn get_account_types() -> Vec<(GovernanceAccountType, AccountVersion)>;
+
+    fn decode_account_info(program_id: &Pubkey, account_info: &AccountInfo) -> Result<Self,ProgramError>;
+}
+
+impl VersionedAccount for RealmV2 {
+    const NAME: &'static str = "realm";
+
+    fn get_account_types() -> Vec<(GovernanceAccountType, AccountVersion)> {
+        vec![
+            (GovernanceAccountType::RealmV1, AccountVersion::V1),
+            (GovernanceAccountType::RealmV2, AccountVersion::V2),
+        ]
+    }
+
+    fn decode_account_info(program_id: &Pubkey, account_info: &AccountInfo) -> Result<Self,ProgramError> {
+        get_realm_data(program_id, account_info)
+    }
+}
+
+impl VersionedAccount for TokenOwnerRecordV2 {
+    const NAME: &'static str = "token owner record";
+
+    fn get_account_types() -> Vec<(GovernanceAccountType, AccountVersion)> {
+        vec![
+            (GovernanceAccountType::TokenOwnerRecordV1, AccountVersion::V1),
+            (GovernanceAccountType::TokenOwnerRecordV2, AccountVersion::V2),
+        ]
+    }
+
+    fn decode_account_info(program_id: &Pubkey, account_info: &AccountInfo) -> Result<Self,ProgramError> {
+        get_token_owner_record_data(program_id, account_info)
+    }
+}
+
+impl VersionedAccount for GovernanceV2 {
+    const NAME: &'static str = "governance";
+
+    /// Governances of all kinds (account, program, mint, token)
+    fn get_account_types() -> Vec<(GovernanceAccountType, AccountVersion)> {
+        vec![
+            (GovernanceAccountType::AccountGovernanceV1, AccountVersion::V1),
+            (GovernanceAccountType::ProgramGovernanceV1, AccountVersion::V1),
+            (GovernanceAccountType::MintGovernanceV1, AccountVersion::V1),
+            (GovernanceAccountType::TokenGovernanceV1, AccountVersion::V1),
+            (GovernanceAccountType::AccountGovernanceV2, AccountVersion::V2),
+            (GovernanceAccountType::ProgramGovernanceV2, AccountVersion::V2),
+            (GovernanceAccountType::MintGovernanceV2, AccountVersion::V2),
+            (GovernanceAccountType::TokenGovernanceV2, AccountVersion::V2),
+        ]
+    }
+
+    fn decode_account_info(program_id: &Pubkey, account_info: &AccountInfo) -> Result<Self,ProgramError> {
+        get_governance_data(program_id, account_info)
+    }
+}
+
+impl VersionedAccount for ProposalV2 {
+    const NAME: &'static str = "proposal";
+
+    fn get_account_types() -> Vec<(GovernanceAccountType, AccountVersion)> {
+        vec![
+            (GovernanceAccountType::ProposalV1, AccountVersion::V1),
+            (GovernanceAccountType::ProposalV2, AccountVersion::V2),
+        ]
+    }
+
+    fn decode_account_info(program_id: &Pubkey, account_info: &AccountInfo) -> Result<Self,ProgramError> {
+        get_proposal_data(program_id, account_info)
+    }
+}
+
+impl VersionedAccount for VoteRecordV2 {
+    const NAME: &'static str = "vote record";
+
+    fn get_account_types() -> Vec<(GovernanceAccountType, AccountVersion)> {
+        vec![
+            (GovernanceAccountType::VoteRecordV1, AccountVersion::V1),
+            (GovernanceAccountType::VoteRecordV2, AccountVersion::V2),
+        ]
+    }
+
+    fn decode_account_info(program_id: &Pubkey, account_info: &AccountInfo) -> Result<Self,ProgramError> {
+        get_vote_record_data(program_id, account_info)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    Empty {
+        address: Pubkey,
+    },
+    /// First byte is not a `GovernanceAccountType`
+    UnknownAccountType {
+        address: Pubkey,
+        account_type: u8,
+    },
+    WrongAccountType {
+        address: Pubkey,
+        expected: &'static str,
+        actual: GovernanceAccountType,
+    },
+    /// Account type matches but the program rejected the data (owner, layout)
+    Invalid {
+        address: Pubkey,
+        expected: &'static str,
+        error: ProgramError,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Empty { address } => write!(f, "Account {} has no data", address),
+            DecodeError::UnknownAccountType { address, account_type } => write!(f, "Account {} has unknown account type {}", address, account_type),
+            DecodeError::WrongAccountType { address, expected, actual } => write!(f, "Account {} is not a {}: account type {:?}", address, expected, actual),
+            DecodeError::Invalid { address, expected, error } => write!(f, "Account {} is not a valid {}: {}", address, expected, error),
+        }
+    }
+}
+
+/// Account type and layout version of governance account data, checked against the types of `T`
+pub fn get_version<T: VersionedAccount>(address: &Pubkey, data: &[u8]) -> Result<AccountVersion,DecodeError> {
+    if data.is_empty() {
+        return Err(DecodeError::Empty { address: *address });
+    }
+    let mut dt: &[u8] = &data[..1];
+    let account_type: GovernanceAccountType = GovernanceAccountType::deserialize(&mut dt)
+        .map_err(|_| DecodeError::UnknownAccountType { address: *address, account_type: data[0] } )?;
+
+    let version: Option<AccountVersion> =
+        T::get_account_types().into_iter()
+            .find(|(expected_type, _)| *expected_type == account_type )
+            .map(|(_, version)| version );
+    version.ok_or(DecodeError::WrongAccountType { address: *address, expected: T::NAME, actual: account_type })
+}
+
+/// Checks the account type first, then decodes V1 or V2 data into the V2 structure
+pub fn decode<T: VersionedAccount>(program_id: &Pubkey, address: &Pubkey, account: &Account) -> Result<Versioned<T>,DecodeError> {
+    let version: AccountVersion = get_version::<T>(address, &account.data)?;
+
+    let mut account: Account = account.clone();
+    let account_info: AccountInfo = (address, &mut account).into_account_info();
+    let data: T = T::decode_account_info(program_id, &account_info)
+        .map_err(|error| DecodeError::Invalid { address: *address, expected: T::NAME, error } )?;
+
+    Ok(Versioned { version, data })
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use governance_test_scripts::address_book::AddressBook;
+use governance_test_scripts::commands::TokenOwner;
+use common::MockRealm;
+use governance_test_scripts::manifest::{ AccountKind, Manifest };
+use governance_test_scripts::output::{ Output, OutputFormat };
+
+#[test]
+fn test_names_and_resolution() {
+    let path: PathBuf = env::temp_dir().join(format!("address-book-{}.toml", Pubkey::new_unique()));
+    let voter1: Pubkey = Pubkey::new_unique();
+    let treasury: Pubkey = Pubkey::new_unique();
+    fs::write(&path, format!("voter1 = \"{}\"\n\"neon treasury\" = \"{}\"\n", voter1, treasury)).unwrap();
+
+    let mut address_book: AddressBook = AddressBook::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let governance: Pubkey = Pubkey::new_unique();
+    let mut manifest: Manifest = Manifest::default();
+    manifest.set_label(&voter1, "first voter");
+    manifest.insert(AccountKind::Governance, "wBAL", &governance);
+    address_book.add_manifest(&manifest);
+
+    // The file comes first, the manifest label of voter1 does not replace its name
+    assert_eq!(address_book.get_name(&voter1), Some("voter1"));
+    assert_eq!(address_book.get_name(&governance), Some("governance wBAL"));
+
+    assert_eq!(address_book.resolve("neon treasury"), Ok(treasury));
+    assert_eq!(address_book.resolve("governance wBAL"), Ok(governance));
+    assert_eq!(address_book.resolve(&voter1.to_string()), Ok(voter1));
+    assert!(address_book.resolve("voter9").is_err());
+
+    let unknown: Pubkey = Pubkey::new_unique();
+    assert_eq!(address_book.annotate(&unknown), unknown.to_string());
+    assert_eq!(
+        address_book.annotate_text(&format!("voter: {}, governance {}: {}.", voter1, governance, unknown)),
+        format!("voter: {} (voter1), governance {} (governance wBAL): {}.", voter1, governance, unknown)
+    );
+
+    assert!(AddressBook::load_or_default(&path).unwrap().get_name(&voter1).is_none());
+}
+
+#[test]
+fn test_output_shows_names() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40]);
+    let voter: &TokenOwner = &mock_realm.voters[0];
+
+    let mut address_book: AddressBook = AddressBook::default();
+    address_book.insert("voter1", &voter.get_owner_pubkey());
+    let output: Output = Output::new(OutputFormat::Table).with_address_book(Arc::new(address_book));
+
+    let table: String = output.render(voter);
+    assert!(table.contains(&format!("{} (voter1)", voter.get_owner_pubkey())), "{}", table);
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use solana_sdk::{
+    instruction::{ Instruction },
+    packet::{ PACKET_DATA_SIZE },
+    pubkey::{ Pubkey },
+    signer::{
+        Signer,
+        keypair::{ Keypair },
+    },
+    system_instruction,
+};
+
+use governance_test_scripts::backend::ProgramTestBackend;
+use governance_test_scripts::commands::SplGovernanceInteractor;
+use governance_test_scripts::offline::DurableNonce;
+
+#[test]
+fn test_operation_larger_than_packet() {
+    let program_id: Pubkey = Pubkey::new_unique();
+    let fixed_weights_addin_id: Pubkey = Pubkey::new_unique();
+    let mock_addin_id: Pubkey = Pubkey::new_unique();
+
+    let backend: ProgramTestBackend = ProgramTestBackend::new(ProgramTestBackend::program_test(&program_id, &fixed_weights_addin_id, &mock_addin_id));
+    let payer: Arc<dyn Signer> = Arc::new(backend.get_payer());
+    let interactor: SplGovernanceInteractor = SplGovernanceInteractor::new_with_backend(Box::new(backend), program_id, mock_addin_id, payer);
+
+    let instruction: Instruction = Instruction::new_with_bytes(program_id, &[0; PACKET_DATA_SIZE], vec![]);
+    let mut batch = interactor.batch();
+    batch.add(vec![ instruction ], vec![]);
+
+    let err = batch.execute().unwrap_err();
+    assert!(err.to_string().contains("does not fit into a transaction"), "{}", err);
+}
+
+#[test]
+fn test_pack_with_durable_nonce() {
+    let program_id: Pubkey = Pubkey::new_unique();
+    let backend: ProgramTestBackend = ProgramTestBackend::new(ProgramTestBackend::program_test(&program_id, &Pubkey::new_unique(), &Pubkey::new_unique()));
+    let payer: Arc<dyn Signer> = Arc::new(backend.get_payer());
+    let interactor: SplGovernanceInteractor = SplGovernanceInteractor::new_with_backend(Box::new(backend), program_id, Pubkey::new_unique(), payer.clone());
+
+    // Transfers to new accounts, as many as fit into a single transaction without a durable nonce
+    let mut transfers: Vec<Instruction> = Vec::new();
+    loop {
+        let mut batch = interactor.batch();
+        for transfer in transfers.iter() {
+            batch.add(vec![ transfer.clone() ], vec![]);
+        }
+        batch.add(vec![ system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1) ], vec![]);
+        if batch.get_transaction_sizes().unwrap().len() > 1 {
+            break;
+        }
+        transfers.push(system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1));
+    }
+
+    let durable_nonce: DurableNonce = interactor.setup_durable_nonce(Arc::new(Keypair::new()), Arc::new(Keypair::new())).unwrap();
+    let interactor: SplGovernanceInteractor = interactor.with_durable_nonce(durable_nonce);
+
+    // The nonce instruction, its accounts and the authority signature push the last transfer out
+    let mut batch = interactor.batch();
+    for transfer in transfers.iter() {
+        batch.add(vec![ transfer.clone() ], vec![]);
+    }
+    let transaction_sizes: Vec<usize> = batch.get_transaction_sizes().unwrap();
+    assert_eq!(transaction_sizes.len(), 2);
+    assert!(transaction_sizes.iter().all(|size| *size <= PACKET_DATA_SIZE ), "{:?}", transaction_sizes);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use solana_sdk::{
+    clock::{ Clock },
+    instruction::{ Instruction },
+    pubkey::{ Pubkey },
+};
+
+use spl_governance::{
+    error::{ GovernanceError },
+    state::{
+        enums::{
+            ProposalState,
+            VoteTipping,
+        },
+        governance::{ GovernanceConfig },
+    },
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::negative::get_governance_error;
+
+const MAX_VOTER_WEIGHT: u64 = 100;
+const VOTER_WEIGHTS: [u64; 3] = [40, 30, 5];
+const MAX_VOTING_TIME: u32 = 3 * 24 * 3600;
+const HOLD_UP_TIME: u32 = 24 * 3600;
+
+#[test]
+fn test_advance_clock() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let clock: Clock = mock_realm.interactor.get_clock().unwrap();
+
+    let advanced_clock: Clock = mock_realm.interactor.advance_clock(MAX_VOTING_TIME as u64).unwrap();
+    assert!(advanced_clock.unix_timestamp >= clock.unix_timestamp + MAX_VOTING_TIME as i64);
+    assert!(advanced_clock.slot > clock.slot);
+    assert_eq!(mock_realm.interactor.get_clock().unwrap(), advanced_clock);
+
+    let warped_clock: Clock = mock_realm.interactor.warp_to_slot(advanced_clock.slot + 100).unwrap();
+    assert_eq!(warped_clock.slot, advanced_clock.slot + 100);
+}
+
+#[test]
+fn test_disabled_tipping_succeeds_at_finalization() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Disabled, MAX_VOTING_TIME, 0, 10));
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[0], true).unwrap();
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[1], true).unwrap();
+    assert_eq!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Voting);
+
+    let error = interactor.finalize_vote(&mock_realm.realm, &governance, &proposal).unwrap_err();
+    assert_eq!(get_governance_error(&error), Some(GovernanceError::CannotFinalizeVotingInProgress));
+
+    interactor.advance_past_voting_end(&governance, &proposal).unwrap();
+    interactor.finalize_vote(&mock_realm.realm, &governance, &proposal).unwrap();
+    assert_eq!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Succeeded);
+}
+
+#[test]
+fn test_below_threshold_defeated_at_finalization() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, MAX_VOTING_TIME, 0, 10));
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[0], true).unwrap();
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[2], false).unwrap();
+    assert_eq!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Voting);
+
+    interactor.advance_past_voting_end(&governance, &proposal).unwrap();
+    interactor.finalize_vote(&mock_realm.realm, &governance, &proposal).unwrap();
+    assert_eq!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Defeated);
+}
+
+#[test]
+fn test_execute_after_hold_up_time() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let interactor = &mock_realm.interactor;
+    let governed_account_pubkey: Pubkey = Pubkey::new_unique();
+    let config: GovernanceConfig = gov_config(60, VoteTipping::Strict, MAX_VOTING_TIME, HOLD_UP_TIME, 10);
+    let governance: Governance = interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, config).unwrap();
+
+    // The proposal changes the config of its own governance
+    let new_config: GovernanceConfig = gov_config(60, VoteTipping::Strict, MAX_VOTING_TIME, HOLD_UP_TIME, 20);
+    let set_governance_config_instruction: Instruction = interactor.set_governance_config_instruction(&governance, new_config.clone());
+    let proposal: Proposal = mock_realm.create_proposal(&governance, &mock_realm.voters[0]).unwrap();
+    let proposal_transaction_address: Pubkey =
+        interactor.insert_transaction(&governance, &proposal, &mock_realm.voters[0], 0, HOLD_UP_TIME, vec![ set_governance_config_instruction ]).unwrap();
+    let proposal: Proposal = interactor.sign_off_proposal(&mock_realm.realm, &governance, proposal, &mock_realm.voters[0]).unwrap();
+
+    // 40 + 30 of 100 tips the proposal to succeeded
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[0], true).unwrap();
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[1], true).unwrap();
+    assert_eq!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Succeeded);
+
+    let error = interactor.execute_transaction(&governance, &proposal, &proposal_transaction_address).unwrap_err();
+    assert_eq!(get_governance_error(&error), Some(GovernanceError::CannotExecuteTransactionWithinHoldUpTime));
+
+    interactor.advance_past_hold_up_time(&proposal, HOLD_UP_TIME).unwrap();
+    interactor.execute_transaction(&governance, &proposal, &proposal_transaction_address).unwrap();
+    assert_eq!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Completed);
+    assert_eq!(interactor.get_governance_v2(&mock_realm.realm.get_name(), &governed_account_pubkey).unwrap().config, new_config);
+}
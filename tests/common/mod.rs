@@ -0,0 +1,80 @@
+// Shared by the integration tests, each of which uses only part of it
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use solana_sdk::{
+    clock::{ Slot },
+    pubkey::{ Pubkey },
+    signer::{ Signer },
+};
+
+use solana_client::client_error::Result as ClientResult;
+
+use spl_governance::state::{
+    enums::{
+        VoteThresholdPercentage,
+        VoteTipping,
+    },
+    governance::{
+        GovernanceConfig,
+    },
+};
+
+use governance_test_scripts::program_test::{ ProgramTestRealm, create_mock_voter };
+use governance_test_scripts::commands::{ SplGovernanceInteractor, Realm, Governance, Proposal, TokenOwner };
+
+const REALM_NAME: &'static str = "Mock Realm";
+
+/// Realm on an in-process bank whose voter weights come from the mock addin, so any weights
+/// (and weight expiries) can be set up with fresh keypairs. Setup failures panic
+pub struct MockRealm {
+    pub interactor: SplGovernanceInteractor,
+    pub realm: Realm,
+    /// One per entry of `voter_weights`; the first one is also the realm authority
+    pub voters: Vec<TokenOwner>,
+}
+
+impl MockRealm {
+    pub fn new(max_voter_weight: u64, voter_weights: &[u64]) -> Self {
+        let ProgramTestRealm { interactor, realm, voters } = ProgramTestRealm::new_mock(REALM_NAME, max_voter_weight, voter_weights).unwrap();
+        MockRealm {
+            interactor,
+            realm,
+            voters,
+        }
+    }
+
+    /// Token owner record plus mock voter weight record, valid up to `voter_weight_expiry` slot
+    pub fn create_voter(&self, voter: Arc<dyn Signer>, voter_weight: u64, voter_weight_expiry: Option<Slot>) -> TokenOwner {
+        create_mock_voter(&self.interactor, &self.realm, voter, voter_weight, voter_weight_expiry).unwrap()
+    }
+
+    /// Governance of a new (unique) governed account, created by the first voter
+    pub fn create_governance(&self, gov_config: GovernanceConfig) -> Governance {
+        self.interactor.create_governance(&self.realm, &self.voters[0], &Pubkey::new_unique(), gov_config).unwrap()
+    }
+
+    pub fn create_proposal(&self, governance: &Governance, owner: &TokenOwner) -> ClientResult<Proposal> {
+        self.interactor.create_proposal(&self.realm, owner, governance, "Mock Proposal", "", governance.get_proposal_count())
+    }
+
+    /// Proposal of the first voter, signed off and in voting state
+    pub fn create_voting_proposal(&self, governance: &Governance) -> Proposal {
+        let proposal: Proposal = self.create_proposal(governance, &self.voters[0]).unwrap();
+        self.interactor.sign_off_proposal(&self.realm, governance, proposal, &self.voters[0]).unwrap()
+    }
+}
+
+/// `YesVote` threshold config without cool-off or council
+pub fn gov_config(yes_vote_threshold_percentage: u8, vote_tipping: VoteTipping, max_voting_time: u32, min_transaction_hold_up_time: u32, min_community_weight_to_create_proposal: u64) -> GovernanceConfig {
+    GovernanceConfig {
+        vote_threshold_percentage: VoteThresholdPercentage::YesVote(yes_vote_threshold_percentage),
+        min_community_weight_to_create_proposal,
+        min_transaction_hold_up_time,
+        max_voting_time,
+        vote_tipping,
+        proposal_cool_off_time: 0,
+        min_council_weight_to_create_proposal: 0,
+    }
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use std::sync::Arc;
+
+use solana_sdk::signer::{
+    Signer,
+    keypair::{ Keypair },
+};
+
+use spl_governance::state::{
+    enums::VoteTipping,
+    vote_record::{ Vote, VoteRecordV2 },
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal, TokenOwner };
+use common::{ MockRealm, gov_config };
+
+#[test]
+fn test_delegate_votes_for_owner() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let owner: &TokenOwner = &mock_realm.voters[1];
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Disabled, 3600, 0, 10));
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    let delegate: Arc<dyn Signer> = Arc::new(Keypair::new());
+    let stranger: Arc<dyn Signer> = Arc::new(Keypair::new());
+    interactor.set_governance_delegate(&mock_realm.realm, owner, Some(delegate.pubkey())).unwrap();
+
+    // Only the delegate of the record is accepted
+    assert!(interactor.get_delegated_token_owner(&mock_realm.realm, &owner.get_owner_pubkey(), stranger.clone()).is_err());
+    let delegated_owner: TokenOwner = interactor.get_delegated_token_owner(&mock_realm.realm, &owner.get_owner_pubkey(), delegate.clone()).unwrap();
+    assert_eq!(delegated_owner.authority.pubkey(), delegate.pubkey());
+    assert_eq!(delegated_owner.get_owner_pubkey(), owner.get_owner_pubkey());
+    assert_eq!(delegated_owner.get_governance_delegate(), Some(delegate.pubkey()));
+    assert_eq!(delegated_owner.get_token_owner_record_address(), owner.get_token_owner_record_address());
+
+    // The mock voter weight record is not derived from the owner: it is kept from the owner's `TokenOwner`
+    assert!(interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &owner.with_authority(stranger), false).is_err());
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &owner.with_authority(delegate), false).unwrap();
+
+    let proposal: Proposal = interactor.reload_proposal(&proposal).unwrap();
+    assert_eq!(proposal.get_deny_vote_weight(), Some(30));
+
+    let vote_records: Vec<VoteRecordV2> =
+        interactor.get_vote_records(&proposal.get_address()).unwrap().into_iter()
+            .map(|(_, vote_record)| vote_record )
+            .collect();
+    assert_eq!(vote_records.len(), 1);
+    assert_eq!(vote_records[0].governing_token_owner, owner.get_owner_pubkey());
+    assert_eq!(vote_records[0].vote, Vote::Deny);
+}
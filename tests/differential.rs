@@ -0,0 +1,136 @@
+mod common;
+
+use proptest::prelude::*;
+
+use spl_governance::state::{
+    enums::{
+        ProposalState,
+        VoteTipping,
+    },
+    governance::GovernanceConfig,
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal, yes_no_vote };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::tally::{ VoteTally, cast_vote_and_compare, finalize_vote_and_compare };
+
+const MAX_VOTING_TIME: u32 = 3600;
+const MIN_COMMUNITY_WEIGHT_TO_CREATE_PROPOSAL: u64 = 1;
+
+/// Realm, governance config and vote order run on a fresh in-process bank, where every step
+/// must match the `VoteTally` prediction. Generated randomly by `scenario_strategy`
+#[derive(Debug,Clone)]
+struct DifferentialScenario {
+    max_voter_weight: u64,
+    /// Weights must be at least `MIN_COMMUNITY_WEIGHT_TO_CREATE_PROPOSAL`, the first voter creates the proposal
+    voter_weights: Vec<u64>,
+    yes_vote_threshold_percentage: u8,
+    vote_tipping: VoteTipping,
+    /// (voter index, yes/no) in casting order, each voter at most once
+    votes: Vec<(usize, bool)>,
+}
+
+impl DifferentialScenario {
+    /// Casts the votes until the proposal leaves voting state, then finalizes if needed.
+    /// Returns the final state, `Err` describes the first divergence between the chain and the model
+    fn run(&self) -> Result<ProposalState,String> {
+        let mock_realm: MockRealm = MockRealm::new(self.max_voter_weight, &self.voter_weights);
+        let interactor = &mock_realm.interactor;
+        let config: GovernanceConfig = gov_config(self.yes_vote_threshold_percentage, self.vote_tipping.clone(), MAX_VOTING_TIME, 0, MIN_COMMUNITY_WEIGHT_TO_CREATE_PROPOSAL);
+        let governance: Governance = mock_realm.create_governance(config.clone());
+        let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+        let mut tally: VoteTally = VoteTally::new(&config, interactor.get_max_vote_weight(&mock_realm.realm).map_err(|err| err.to_string() )?, &proposal.data)?;
+
+        for (voter_index, vote_yes_no) in self.votes.iter() {
+            if tally.get_state() != ProposalState::Voting {
+                break;
+            }
+            let mismatches: Vec<String> =
+                cast_vote_and_compare(interactor, &mut tally, &mock_realm.realm, &governance, &proposal, &mock_realm.voters[*voter_index], yes_no_vote(*vote_yes_no))
+                    .map_err(|err| format!("vote of voter {} failed: {}", voter_index, err) )?;
+            if !mismatches.is_empty() {
+                return Err(format!("after vote of voter {}: {:?}", voter_index, mismatches));
+            }
+        }
+
+        if tally.get_state() == ProposalState::Voting {
+            interactor.advance_past_voting_end(&governance, &proposal)
+                .map_err(|err| format!("advancing clock failed: {}", err) )?;
+            let mismatches: Vec<String> =
+                finalize_vote_and_compare(interactor, &mut tally, &mock_realm.realm, &governance, &proposal)
+                    .map_err(|err| format!("finalization failed: {}", err) )?;
+            if !mismatches.is_empty() {
+                return Err(format!("after finalization: {:?}", mismatches));
+            }
+        }
+
+        Ok(tally.get_state())
+    }
+}
+
+/// Every case spins up its own bank, so the default 256 cases are too slow; `PROPTEST_CASES` is ignored here
+const CASES: u32 = 32;
+
+fn vote_tipping_strategy() -> impl Strategy<Value = VoteTipping> {
+    prop_oneof![
+        Just(VoteTipping::Strict),
+        Just(VoteTipping::Early),
+        Just(VoteTipping::Disabled),
+    ]
+}
+
+/// Up to 6 voters, max voter weight at least the sum of the voter weights, some of the voters vote in random order
+fn scenario_strategy() -> impl Strategy<Value = DifferentialScenario> {
+    (prop::collection::vec(1..=50u64, 2..=6), 0..=50u64, 1..=100u8, vote_tipping_strategy())
+        .prop_flat_map(|(voter_weights, extra_max_voter_weight, yes_vote_threshold_percentage, vote_tipping)| {
+            let voters_count: usize = voter_weights.len();
+            (
+                Just(voter_weights),
+                Just(extra_max_voter_weight),
+                Just(yes_vote_threshold_percentage),
+                Just(vote_tipping),
+                Just((0..voters_count).collect::<Vec<usize>>()).prop_shuffle(),
+                prop::collection::vec(any::<bool>(), voters_count),
+                0..=voters_count,
+            )
+        })
+        .prop_map(|(voter_weights, extra_max_voter_weight, yes_vote_threshold_percentage, vote_tipping, voter_order, votes_yes_no, votes_count)| {
+            DifferentialScenario {
+                max_voter_weight: voter_weights.iter().sum::<u64>() + extra_max_voter_weight,
+                voter_weights,
+                yes_vote_threshold_percentage,
+                vote_tipping,
+                votes: voter_order.into_iter().zip(votes_yes_no.into_iter()).take(votes_count).collect(),
+            }
+        })
+}
+
+/// Like `scenario_strategy`, with the max voter weight below the sum of the voter weights,
+/// so the votes cast can exceed the max voter weight record
+fn exceeding_max_scenario_strategy() -> impl Strategy<Value = DifferentialScenario> {
+    scenario_strategy()
+        .prop_flat_map(|scenario| {
+            let voter_weights_sum: u64 = scenario.voter_weights.iter().sum();
+            (Just(scenario), 1..voter_weights_sum)
+        })
+        .prop_map(|(scenario, max_voter_weight)| DifferentialScenario { max_voter_weight, ..scenario } )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn test_chain_matches_tally(scenario in scenario_strategy()) {
+        if let Err(err) = scenario.run() {
+            prop_assert!(false, "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_chain_matches_tally_above_max_voter_weight(scenario in exceeding_max_scenario_strategy()) {
+        if let Err(err) = scenario.run() {
+            prop_assert!(false, "{}", err);
+        }
+    }
+}
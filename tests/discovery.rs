@@ -0,0 +1,54 @@
+mod common;
+
+use solana_sdk::{
+    account::{ Account },
+    pubkey::{ Pubkey },
+};
+
+use spl_governance::state::enums::VoteTipping;
+
+use governance_test_scripts::commands::{ Governance, Proposal };
+use common::{ MockRealm, gov_config };
+
+#[test]
+fn test_enumerate_accounts() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30, 5]);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, 3600, 0, 10));
+    let other_governance: Governance = mock_realm.create_governance(gov_config(50, VoteTipping::Early, 3600, 0, 10));
+    let proposal: Proposal = mock_realm.create_proposal(&governance, &mock_realm.voters[0]).unwrap();
+
+    let realms: Vec<Pubkey> = interactor.get_realms().unwrap().into_iter().map(|(address, _)| address ).collect();
+    assert_eq!(realms, vec![ mock_realm.realm.address ]);
+
+    let mut governances: Vec<Pubkey> = interactor.get_governances(&mock_realm.realm.address).unwrap().into_iter().map(|(address, _)| address ).collect();
+    governances.sort();
+    let mut expected_governances: Vec<Pubkey> = vec![ governance.address, other_governance.address ];
+    expected_governances.sort();
+    assert_eq!(governances, expected_governances);
+
+    let proposals: Vec<Pubkey> = interactor.get_proposals(&governance.address).unwrap().into_iter().map(|(address, _)| address ).collect();
+    assert_eq!(proposals, vec![ proposal.get_address() ]);
+    assert!(interactor.get_proposals(&other_governance.address).unwrap().is_empty());
+
+    assert_eq!(interactor.get_token_owner_records(&mock_realm.realm.address).unwrap().len(), mock_realm.voters.len());
+    assert!(interactor.get_token_owner_records(&Pubkey::new_unique()).unwrap().is_empty());
+}
+
+#[test]
+fn test_enumerate_set_accounts() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40]);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, 3600, 0, 10));
+
+    // Never referenced by a transaction
+    let copied_governance: Pubkey = Pubkey::new_unique();
+    let account: Account = interactor.get_account(&governance.address).unwrap().unwrap();
+    interactor.set_account(&copied_governance, &account).unwrap();
+
+    let mut governances: Vec<Pubkey> = interactor.get_governances(&mock_realm.realm.address).unwrap().into_iter().map(|(address, _)| address ).collect();
+    governances.sort();
+    let mut expected_governances: Vec<Pubkey> = vec![ governance.address, copied_governance ];
+    expected_governances.sort();
+    assert_eq!(governances, expected_governances);
+}
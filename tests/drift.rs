@@ -0,0 +1,57 @@
+mod common;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use spl_governance::state::enums::VoteTipping;
+
+use governance_test_scripts::commands::{ Governance, Realm };
+use governance_test_scripts::drift::{ Drift, DriftPolicy };
+use common::{ MockRealm, gov_config };
+
+#[test]
+fn test_unchanged_governance_has_no_drift() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let governed_account_pubkey: Pubkey = Pubkey::new_unique();
+
+    interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(60, VoteTipping::Strict, 3600, 0, 10)).unwrap();
+    interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(60, VoteTipping::Strict, 3600, 0, 10)).unwrap();
+    assert!(interactor.take_drifts().is_empty());
+}
+
+#[test]
+fn test_governance_config_drift() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let governed_account_pubkey: Pubkey = Pubkey::new_unique();
+    let governance: Governance = interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(60, VoteTipping::Strict, 3600, 0, 10)).unwrap();
+
+    // Warn: the existing governance is returned
+    let existing: Governance = interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(50, VoteTipping::Strict, 3600, 0, 10)).unwrap();
+    assert_eq!(existing.address, governance.address);
+    let drifts: Vec<Drift> = interactor.take_drifts();
+    assert_eq!(drifts.len(), 1);
+    assert_eq!(drifts[0].address, governance.address);
+    assert_eq!(drifts[0].field, "config");
+
+    interactor.set_drift_policy(DriftPolicy::Error);
+    assert!(interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(50, VoteTipping::Strict, 3600, 0, 10)).is_err());
+    assert_eq!(interactor.take_drifts().len(), 1);
+
+    interactor.set_drift_policy(DriftPolicy::Ignore);
+    interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(50, VoteTipping::Strict, 3600, 0, 10)).unwrap();
+    assert!(interactor.take_drifts().is_empty());
+}
+
+#[test]
+fn test_realm_authority_drift() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    interactor.set_drift_policy(DriftPolicy::Error);
+
+    let result: Result<Realm,_> = interactor.create_realm(mock_realm.voters[1].authority.clone(), &mock_realm.realm.get_community_mint(), None, &mock_realm.realm.get_name());
+    assert!(result.is_err());
+    let drifts: Vec<Drift> = interactor.take_drifts();
+    assert!(drifts.iter().any(|drift| drift.field == "authority" && drift.address == mock_realm.realm.address ));
+    assert!(drifts.iter().any(|drift| drift.field == "use_community_voter_weight_addin" ));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use solana_sdk::{
+    pubkey::{ Pubkey },
+};
+
+use spl_governance::{
+    error::{ GovernanceError },
+    state::{
+        enums::{ VoteTipping },
+    },
+};
+
+use governance_test_scripts::commands::{ SplGovernanceInteractor, ExecutionMode, Outcome, Realm, Governance, Proposal, TokenOwner };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::negative::get_governance_error;
+
+const MAX_VOTER_WEIGHT: u64 = 100;
+const VOTER_WEIGHTS: [u64; 2] = [40, 30];
+const MAX_VOTING_TIME: u32 = 3600;
+
+/// Voting proposal of the first voter, set up by sent transactions; the returned interactor only simulates
+fn setup() -> (SplGovernanceInteractor, Realm, Vec<TokenOwner>, Governance, Proposal) {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, MAX_VOTING_TIME, 0, 1));
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    let MockRealm { interactor, realm, voters } = mock_realm;
+    (interactor.with_execution_mode(ExecutionMode::DryRun), realm, voters, governance, proposal)
+}
+
+#[test]
+fn test_dry_run_vote_is_simulated() {
+    let (interactor, realm, voters, governance, proposal) = setup();
+
+    match interactor.cast_vote(&realm, &governance, &proposal, &voters[0], true).unwrap() {
+        Outcome::Simulated(report) => {
+            assert!(report.is_success());
+            assert!(report.units_consumed.unwrap() > 0);
+            assert!(!report.logs.is_empty());
+        },
+        outcome => panic!("Unexpected outcome {}", outcome),
+    }
+    assert_eq!(interactor.take_outcomes().len(), 1);
+
+    // Nothing is committed
+    let proposal: Proposal = interactor.reload_proposal(&proposal).unwrap();
+    assert_eq!(proposal.data.options[0].vote_weight, 0);
+}
+
+#[test]
+fn test_dry_run_failure_is_decoded() {
+    let (interactor, realm, voters, governance, proposal) = setup();
+    interactor.advance_past_voting_end(&governance, &proposal).unwrap();
+
+    let error = interactor.cast_vote(&realm, &governance, &proposal, &voters[1], true).unwrap_err();
+    assert_eq!(get_governance_error(&error), Some(GovernanceError::ProposalVotingTimeExpired));
+
+    match &interactor.take_outcomes()[..] {
+        [ Outcome::Simulated(report) ] => {
+            assert!(!report.is_success());
+            assert_eq!(report.governance_error, Some(GovernanceError::ProposalVotingTimeExpired));
+        },
+        outcomes => panic!("Unexpected outcomes {:?}", outcomes),
+    }
+}
+
+#[test]
+fn test_dry_run_stops_account_creation() {
+    let (interactor, realm, voters, _, _) = setup();
+
+    // The governance would have to be read back after its creation
+    assert!(interactor.create_governance(&realm, &voters[0], &Pubkey::new_unique(), gov_config(60, VoteTipping::Strict, MAX_VOTING_TIME, 0, 1)).is_err());
+    match &interactor.take_outcomes()[..] {
+        [ Outcome::Simulated(report) ] => assert!(report.is_success()),
+        outcomes => panic!("Unexpected outcomes {:?}", outcomes),
+    }
+}
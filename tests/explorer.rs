@@ -0,0 +1,57 @@
+mod common;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use spl_governance::state::enums::VoteTipping;
+
+use governance_test_scripts::commands::{ Governance, Proposal };
+use governance_test_scripts::explorer::{ DerivedAddress, explore_realm };
+use common::{ MockRealm, gov_config };
+
+fn find<'a>(addresses: &'a [DerivedAddress], name: &str) -> &'a DerivedAddress {
+    addresses.iter().find(|address| address.name == name ).unwrap_or_else(|| panic!("{} not derived", name) )
+}
+
+#[test]
+fn test_explore_realm() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Disabled, 3600, 0, 10));
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[0], true).unwrap();
+
+    let owners: Vec<Pubkey> = mock_realm.voters.iter().map(|voter| voter.get_owner_pubkey() ).collect();
+    let governed_account: Pubkey = governance.get_governed_account();
+    let community_mint: Pubkey = mock_realm.realm.get_community_mint();
+    let addresses: Vec<DerivedAddress> = explore_realm(interactor, &mock_realm.realm.get_name(), None, &owners, &[ governed_account ]).unwrap();
+
+    let realm: &DerivedAddress = find(&addresses, &format!("realm {}", mock_realm.realm.get_name()));
+    assert_eq!(realm.address, mock_realm.realm.address);
+    assert!(realm.exists);
+    assert!(find(&addresses, &format!("holding-account {}", community_mint)).exists);
+    for owner in owners.iter() {
+        assert!(find(&addresses, &format!("token-owner-record {} {}", owner, community_mint)).exists);
+    }
+
+    let account_governance: &DerivedAddress = find(&addresses, &format!("account-governance {}", governed_account));
+    assert_eq!(account_governance.address, governance.address);
+    assert!(account_governance.exists);
+    assert!(!find(&addresses, &format!("program-governance {}", governed_account)).exists);
+
+    let proposal_address: &DerivedAddress = find(&addresses, &format!("proposal {} {} 0", governance.address, community_mint));
+    assert_eq!(proposal_address.address, proposal.get_address());
+    assert!(find(&addresses, &format!("vote-record {} {}", proposal.get_address(), owners[0])).exists);
+    assert!(!find(&addresses, &format!("vote-record {} {}", proposal.get_address(), owners[1])).exists);
+}
+
+#[test]
+fn test_explore_missing_realm() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40]);
+    let interactor = &mock_realm.interactor;
+
+    assert!(explore_realm(interactor, "No Such Realm", None, &[], &[]).is_err());
+
+    let addresses: Vec<DerivedAddress> = explore_realm(interactor, "No Such Realm", Some(Pubkey::new_unique()), &[ Pubkey::new_unique() ], &[]).unwrap();
+    assert!(addresses.iter().all(|address| !address.exists ));
+    assert!(addresses.iter().any(|address| address.name.starts_with("token-owner-record ") ));
+}
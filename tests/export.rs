@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::{ PathBuf };
+use std::sync::Arc;
+
+use solana_sdk::{
+    instruction::{ Instruction },
+    pubkey::{ Pubkey },
+    signer::{ Signer },
+    system_instruction,
+};
+
+use governance_test_scripts::backend::ProgramTestBackend;
+use governance_test_scripts::commands::{ SplGovernanceInteractor, ExecutionMode, Outcome };
+use governance_test_scripts::offline::{ self, OfflineTransaction };
+use governance_test_scripts::send::SendStrategy;
+
+#[test]
+fn test_export_continues_numbering() {
+    let directory: PathBuf = std::env::temp_dir().join(format!("export-{}", Pubkey::new_unique()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("transaction-005.json"), "{}").unwrap();
+
+    let program_id: Pubkey = Pubkey::new_unique();
+    let backend: ProgramTestBackend = ProgramTestBackend::new(ProgramTestBackend::program_test(&program_id, &Pubkey::new_unique(), &Pubkey::new_unique()));
+    let payer: Arc<dyn Signer> = Arc::new(backend.get_payer());
+    let interactor: SplGovernanceInteractor =
+        SplGovernanceInteractor::new_with_backend(Box::new(backend), program_id, Pubkey::new_unique(), payer.clone())
+            .with_execution_mode(ExecutionMode::Export(directory.clone()));
+
+    let instruction: Instruction = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+    for index in [6, 7] {
+        match interactor.process_transaction(&[ instruction.clone() ], &[]).unwrap() {
+            Outcome::Exported(path) => assert_eq!(path, directory.join(format!("transaction-{:03}.json", index))),
+            outcome => panic!("Unexpected outcome {}", outcome),
+        }
+    }
+    assert_eq!(fs::read_to_string(directory.join("transaction-005.json")).unwrap(), "{}");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_submit_signed_export() {
+    let directory: PathBuf = std::env::temp_dir().join(format!("export-{}", Pubkey::new_unique()));
+    fs::create_dir_all(&directory).unwrap();
+
+    let program_id: Pubkey = Pubkey::new_unique();
+    let backend: ProgramTestBackend = ProgramTestBackend::new(ProgramTestBackend::program_test(&program_id, &Pubkey::new_unique(), &Pubkey::new_unique()));
+    let payer: Arc<dyn Signer> = Arc::new(backend.get_payer());
+    let interactor: SplGovernanceInteractor =
+        SplGovernanceInteractor::new_with_backend(Box::new(backend), program_id, Pubkey::new_unique(), payer.clone())
+            .with_execution_mode(ExecutionMode::Export(directory.clone()));
+
+    let recipient: Pubkey = Pubkey::new_unique();
+    let instruction: Instruction = system_instruction::transfer(&payer.pubkey(), &recipient, 1_000_000);
+    let path: PathBuf =
+        match interactor.process_transaction(&[ instruction ], &[]).unwrap() {
+            Outcome::Exported(path) => path,
+            outcome => panic!("Unexpected outcome {}", outcome),
+        };
+
+    // The payer is the only signer and signed on export
+    let offline_transaction: OfflineTransaction = OfflineTransaction::load(&path).unwrap();
+    assert!(offline_transaction.missing_signers().is_empty());
+    assert_eq!(interactor.get_account(&recipient).unwrap(), None);
+
+    offline::submit(interactor.get_backend(), &SendStrategy::default(), &offline_transaction).unwrap();
+    assert_eq!(interactor.get_account(&recipient).unwrap().map(|account| account.lamports ), Some(1_000_000));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use solana_sdk::{
+    pubkey::{ Pubkey },
+    signer::{
+        Signer,
+        keypair::{ read_keypair_file },
+    },
+};
+
+use spl_governance::state::{
+    enums::{
+        ProposalState,
+        VoteThresholdPercentage,
+        VoteTipping,
+    },
+    governance::GovernanceConfig,
+};
+
+use governance_test_scripts::program_test::{ ProgramTestSetup, VoterWeightAddin };
+use governance_test_scripts::batch::TransactionBatch;
+use governance_test_scripts::commands::{ SplGovernanceInteractor, Realm, Governance, Proposal, TokenOwner, yes_no_vote };
+use governance_test_scripts::tally::{ VoteTally, cast_vote_and_compare, finalize_vote_and_compare };
+
+// Voter weights come from the fixed-weights addin, as in `main.rs`: it only knows the keypairs of its
+// built-in voter list, so the weights are read back from the records instead of being set up here
+const VOTER_KEY_FILE_PATHS: [&'static str; 5] = [
+    "../neon-spl-governance/artifacts/voter1.keypair",
+    "../neon-spl-governance/artifacts/voter2.keypair",
+    "../neon-spl-governance/artifacts/voter3.keypair",
+    "../neon-spl-governance/artifacts/voter4.keypair",
+    "../neon-spl-governance/artifacts/voter5.keypair",
+];
+
+const REALM_NAME: &'static str = "_Test_Realm_5";
+const PROPOSAL_NAME: &'static str = "Proposal To Vote";
+const PROPOSAL_DESCRIPTION: &'static str = "proposal_description";
+
+struct Setup {
+    interactor: SplGovernanceInteractor,
+    community_mint_pubkey: Pubkey,
+    governed_account_pubkey: Pubkey,
+    addin_pubkey: Pubkey,
+    voters: Vec<Arc<dyn Signer>>,
+}
+
+fn setup() -> Setup {
+    let ProgramTestSetup { interactor, community_mint, voter_weight_addin } = ProgramTestSetup::new(VoterWeightAddin::FixedWeights).unwrap();
+    let voters: Vec<Arc<dyn Signer>> =
+        VOTER_KEY_FILE_PATHS.iter()
+            .map(|path| Arc::new(read_keypair_file(path).unwrap()) as Arc<dyn Signer> )
+            .collect();
+
+    Setup {
+        interactor,
+        community_mint_pubkey: community_mint,
+        governed_account_pubkey: Pubkey::new_unique(),
+        addin_pubkey: voter_weight_addin,
+        voters,
+    }
+}
+
+fn gov_config() -> GovernanceConfig {
+    GovernanceConfig {
+        vote_threshold_percentage: VoteThresholdPercentage::YesVote(60),
+        min_community_weight_to_create_proposal: 10,
+        min_transaction_hold_up_time: 0,
+        max_voting_time: 78200,
+        vote_tipping: VoteTipping::Strict,
+        proposal_cool_off_time: 0,
+        min_council_weight_to_create_proposal: 0,
+    }
+}
+
+/// Everything `main.rs` does up to (not including) the votes; safe to call repeatedly
+fn create_realm_to_proposal(setup: &Setup) -> (Realm, Vec<TokenOwner>, Governance, Proposal) {
+    let interactor: &SplGovernanceInteractor = &setup.interactor;
+
+    // Realm authority is also the first voter
+    let realm: Realm = interactor.create_realm(setup.voters[0].clone(), &setup.community_mint_pubkey, Some(setup.addin_pubkey), REALM_NAME).unwrap();
+    interactor.setup_max_voter_weight_record_fixed(&realm).unwrap();
+
+    // Token owner records and voter weight records of all voters in as few transactions as possible
+    let mut batch: TransactionBatch = interactor.batch();
+    for voter in setup.voters.iter() {
+        batch
+            .create_token_owner_record(&realm, &voter.pubkey())
+            .setup_voter_weight_record_fixed(&realm, &voter.pubkey());
+    }
+    batch.execute().unwrap();
+
+    let token_owners: Vec<TokenOwner> =
+        setup.voters.iter()
+            .map(|voter| interactor.get_token_owner(&realm, voter.clone()).unwrap() )
+            .collect();
+
+    let governance: Governance = interactor.create_governance(&realm, &token_owners[0], &setup.governed_account_pubkey, gov_config()).unwrap();
+    let proposal: Proposal = interactor.create_proposal(&realm, &token_owners[0], &governance, PROPOSAL_NAME, PROPOSAL_DESCRIPTION, 0).unwrap();
+    let proposal: Proposal =
+        if proposal.data.state == ProposalState::Draft {
+            interactor.sign_off_proposal(&realm, &governance, proposal, &token_owners[0]).unwrap()
+        } else {
+            proposal
+        };
+
+    (realm, token_owners, governance, proposal)
+}
+
+/// Tally of the proposal with the max vote weight of the realm
+fn new_tally(interactor: &SplGovernanceInteractor, realm: &Realm, proposal: &Proposal) -> VoteTally {
+    VoteTally::new(&gov_config(), interactor.get_max_vote_weight(realm).unwrap(), &proposal.data).unwrap()
+}
+
+/// Casts the votes while the proposal is voting, each one must leave the proposal as the tally predicts
+fn cast_votes(interactor: &SplGovernanceInteractor, tally: &mut VoteTally, realm: &Realm, governance: &Governance, proposal: &Proposal, votes: &[(&TokenOwner, bool)]) {
+    for (voter, vote_yes_no) in votes.iter() {
+        if tally.get_state() != ProposalState::Voting {
+            break;
+        }
+        let mismatches: Vec<String> = cast_vote_and_compare(interactor, tally, realm, governance, proposal, voter, yes_no_vote(*vote_yes_no)).unwrap();
+        assert!(mismatches.is_empty(), "{:?}", mismatches);
+    }
+}
+
+#[test]
+fn test_realm_setup() {
+    let setup: Setup = setup();
+    let (realm, token_owners, _governance, proposal) = create_realm_to_proposal(&setup);
+
+    assert_eq!(realm.get_name(), REALM_NAME);
+    assert_eq!(realm.get_community_mint(), setup.community_mint_pubkey);
+    assert_eq!(realm.address, setup.interactor.get_realm_address(REALM_NAME));
+    assert!(setup.interactor.get_max_vote_weight(&realm).unwrap() > 0);
+
+    for (token_owner, voter) in token_owners.iter().zip(setup.voters.iter()) {
+        assert_eq!(token_owner.get_owner_pubkey(), voter.pubkey());
+        assert_eq!(token_owner.get_governance_delegate(), None);
+        assert_eq!(token_owner.get_voter_weight_record_address(), Some(setup.interactor.get_voter_weight_record_address_fixed(&realm, &voter.pubkey())));
+        assert!(setup.interactor.get_voter_weight(token_owner).unwrap() > 0);
+    }
+
+    assert_eq!(proposal.data.state, ProposalState::Voting);
+    assert_eq!(proposal.data.name, PROPOSAL_NAME);
+    assert_eq!(proposal.get_option_vote_weights(), vec![ ("Yes".to_string(), 0) ]);
+    assert_eq!(proposal.get_deny_vote_weight(), Some(0));
+}
+
+#[test]
+fn test_votes_tally() {
+    let setup: Setup = setup();
+    let interactor: &SplGovernanceInteractor = &setup.interactor;
+    let (realm, token_owners, governance, proposal) = create_realm_to_proposal(&setup);
+
+    interactor.cast_vote(&realm, &governance, &proposal, &token_owners[0], true).unwrap();
+    interactor.cast_vote(&realm, &governance, &proposal, &token_owners[1], false).unwrap();
+
+    let proposal: Proposal = interactor.reload_proposal(&proposal).unwrap();
+    assert_eq!(proposal.get_option_vote_weights(), vec![ ("Yes".to_string(), interactor.get_voter_weight(&token_owners[0]).unwrap()) ]);
+    assert_eq!(proposal.get_deny_vote_weight(), Some(interactor.get_voter_weight(&token_owners[1]).unwrap()));
+}
+
+#[test]
+fn test_yes_votes_follow_tally() {
+    let setup: Setup = setup();
+    let interactor: &SplGovernanceInteractor = &setup.interactor;
+    let (realm, token_owners, governance, proposal) = create_realm_to_proposal(&setup);
+    let mut tally: VoteTally = new_tally(interactor, &realm, &proposal);
+
+    let votes: Vec<(&TokenOwner, bool)> = token_owners.iter().map(|token_owner| (token_owner, true) ).collect();
+    cast_votes(interactor, &mut tally, &realm, &governance, &proposal, &votes);
+
+    // Without tipping the votes are counted at the end of the voting time
+    if tally.get_state() == ProposalState::Voting {
+        interactor.advance_past_voting_end(&governance, &proposal).unwrap();
+        let mismatches: Vec<String> = finalize_vote_and_compare(interactor, &mut tally, &realm, &governance, &proposal).unwrap();
+        assert!(mismatches.is_empty(), "{:?}", mismatches);
+    }
+    assert_ne!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Voting);
+}
+
+#[test]
+fn test_deny_votes_follow_tally() {
+    let setup: Setup = setup();
+    let interactor: &SplGovernanceInteractor = &setup.interactor;
+    let (realm, token_owners, governance, proposal) = create_realm_to_proposal(&setup);
+    let mut tally: VoteTally = new_tally(interactor, &realm, &proposal);
+
+    let votes: Vec<(&TokenOwner, bool)> = token_owners.iter().map(|token_owner| (token_owner, false) ).collect();
+    cast_votes(interactor, &mut tally, &realm, &governance, &proposal, &votes);
+
+    if tally.get_state() == ProposalState::Voting {
+        interactor.advance_past_voting_end(&governance, &proposal).unwrap();
+        let mismatches: Vec<String> = finalize_vote_and_compare(interactor, &mut tally, &realm, &governance, &proposal).unwrap();
+        assert!(mismatches.is_empty(), "{:?}", mismatches);
+    }
+    assert_eq!(interactor.reload_proposal(&proposal).unwrap().data.state, ProposalState::Defeated);
+}
+
+#[test]
+fn test_rerun_is_idempotent() {
+    let setup: Setup = setup();
+    let interactor: &SplGovernanceInteractor = &setup.interactor;
+    let (realm, token_owners, governance, proposal) = create_realm_to_proposal(&setup);
+
+    interactor.cast_vote(&realm, &governance, &proposal, &token_owners[0], true).unwrap();
+    let proposal: Proposal = interactor.reload_proposal(&proposal).unwrap();
+
+    // Second run finds every account and sends nothing that changes them
+    let (realm_rerun, token_owners_rerun, governance_rerun, proposal_rerun) = create_realm_to_proposal(&setup);
+    assert_eq!(realm_rerun.address, realm.address);
+    assert_eq!(governance_rerun.get_proposal_count(), 1);
+    for (token_owner, token_owner_rerun) in token_owners.iter().zip(token_owners_rerun.iter()) {
+        assert_eq!(token_owner_rerun.get_owner_pubkey(), token_owner.get_owner_pubkey());
+        assert_eq!(token_owner_rerun.get_voter_weight_record_address(), token_owner.get_voter_weight_record_address());
+    }
+    assert_eq!(proposal_rerun.data, proposal.data);
+    assert!(interactor.setup_max_voter_weight_record_fixed(&realm_rerun).unwrap().is_none());
+    assert!(interactor.batch().create_token_owner_record(&realm_rerun, &setup.voters[0].pubkey()).is_empty());
+
+    // Votes are not: the second one is rejected and the tally stays the same
+    assert!(interactor.cast_vote(&realm_rerun, &governance_rerun, &proposal_rerun, &token_owners_rerun[0], true).is_err());
+    assert_eq!(interactor.reload_proposal(&proposal_rerun).unwrap().data, proposal.data);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use std::env;
+use std::path::PathBuf;
+
+use solana_sdk::{
+    pubkey::{ Pubkey },
+    signer::Signer,
+};
+
+use spl_governance::state::enums::VoteTipping;
+
+use governance_test_scripts::commands::{ Governance, Proposal };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::manifest::{ AccountKind, Manifest };
+
+#[test]
+fn test_created_accounts_are_recorded() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let governed_account_pubkey: Pubkey = Pubkey::new_unique();
+    interactor.set_label(&governed_account_pubkey, "wBAL");
+
+    let governance: Governance = interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(60, VoteTipping::Strict, 3600, 0, 10)).unwrap();
+    let _proposal: Proposal = mock_realm.create_proposal(&governance, &mock_realm.voters[0]).unwrap();
+
+    let manifest: Manifest = interactor.get_manifest();
+    assert_eq!(manifest.get_address("realm Mock Realm"), Some(mock_realm.realm.address));
+    assert_eq!(manifest.get_address("governance wBAL"), Some(governance.address));
+    assert!(manifest.get_address("proposal wBAL 0").is_some());
+    assert_eq!(manifest.get_entries(AccountKind::TokenOwnerRecord).len(), 2);
+    assert_eq!(manifest.get_entries(AccountKind::VoterWeightRecord).len(), 2);
+    let voter_name: String = format!("token-owner-record {}", mock_realm.voters[1].authority.pubkey());
+    assert!(manifest.get_address(&voter_name).is_some());
+}
+
+#[test]
+fn test_manifest_is_reloaded() {
+    let path: PathBuf = env::temp_dir().join(format!("manifest-{}.json", Pubkey::new_unique()));
+    let address: Pubkey = Pubkey::new_unique();
+
+    let mut manifest: Manifest = Manifest::load_or_default(&path).unwrap();
+    assert!(manifest.accounts.is_empty());
+    let name: String = manifest.insert(AccountKind::Governance, "wBAL", &address);
+    manifest.save(&path).unwrap();
+
+    let reloaded: Manifest = Manifest::load_or_default(&path).unwrap();
+    assert_eq!(reloaded.get_address(&name), Some(address));
+    assert_eq!(reloaded.get_name(&address), Some("governance wBAL".to_string()));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_recorded_addresses_are_looked_up() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let governed_account_pubkey: Pubkey = Pubkey::new_unique();
+    interactor.set_label(&governed_account_pubkey, "wBAL");
+
+    let governance: Governance = interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &governed_account_pubkey, gov_config(60, VoteTipping::Strict, 3600, 0, 10)).unwrap();
+    assert_eq!(interactor.get_recorded_address(AccountKind::Governance, "wBAL").unwrap(), governance.address);
+    assert_eq!(interactor.get_manifest_address("governance wBAL").unwrap(), governance.address);
+    assert!(interactor.get_recorded_address(AccountKind::Governance, "wETH").is_err());
+}
+
+#[test]
+fn test_manifest_save_error_fails_the_operation() {
+    let MockRealm { interactor, realm, voters } = MockRealm::new(100, &[40, 30]);
+    let path: PathBuf = env::temp_dir().join(format!("missing-{}", Pubkey::new_unique())).join("manifest.json");
+    let interactor = interactor.with_manifest(path).unwrap();
+
+    assert!(interactor.create_governance(&realm, &voters[0], &Pubkey::new_unique(), gov_config(60, VoteTipping::Strict, 3600, 0, 10)).is_err());
+}
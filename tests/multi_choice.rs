@@ -0,0 +1,46 @@
+mod common;
+
+use spl_governance::state::{
+    enums::{
+        ProposalState,
+        VoteTipping,
+    },
+    proposal::VoteType,
+    vote_record::VoteChoice,
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal };
+use common::{ MockRealm, gov_config };
+
+/// One choice per option, 100% for the approved ones
+fn choices(approved: &[bool]) -> Vec<VoteChoice> {
+    approved.iter()
+        .map(|approved| VoteChoice { rank: 0, weight_percentage: if *approved { 100 } else { 0 } } )
+        .collect()
+}
+
+#[test]
+fn test_option_vote_weights() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30, 20]);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, 3600, 0, 10));
+    let options: Vec<String> = vec![ "Option A".to_string(), "Option B".to_string(), "Option C".to_string() ];
+    let proposal: Proposal =
+        interactor.create_proposal_with_options(&mock_realm.realm, &mock_realm.voters[0], &governance, "Multi Choice", "", governance.get_proposal_count(),
+                                                VoteType::MultiChoice(3), options, true)
+            .unwrap();
+    let proposal: Proposal = interactor.sign_off_proposal(&mock_realm.realm, &governance, proposal, &mock_realm.voters[0]).unwrap();
+
+    interactor.cast_vote_choices(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[0], choices(&[ true, false, true ])).unwrap();
+    interactor.cast_vote_choices(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[1], choices(&[ false, true, false ])).unwrap();
+    interactor.cast_vote_choices(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[2], choices(&[ true, false, false ])).unwrap();
+
+    // Each approved option gets the whole voter weight; multi choice proposals do not tip
+    let proposal: Proposal = interactor.reload_proposal(&proposal).unwrap();
+    assert_eq!(
+        proposal.get_option_vote_weights(),
+        vec![ ("Option A".to_string(), 60), ("Option B".to_string(), 30), ("Option C".to_string(), 40) ]
+    );
+    assert_eq!(proposal.get_deny_vote_weight(), Some(0));
+    assert_eq!(proposal.data.state, ProposalState::Voting);
+}
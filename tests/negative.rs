@@ -0,0 +1,38 @@
+use governance_test_scripts::negative::{ NegativeScenario, catalog };
+
+fn run_scenario(name: &str) {
+    let scenario: NegativeScenario = catalog().into_iter().find(|scenario| scenario.name == name).unwrap();
+    if let Err(err) = scenario.run() {
+        panic!("{}: {}", scenario.name, err);
+    }
+}
+
+#[test]
+fn test_vote_twice() {
+    run_scenario("vote-twice");
+}
+
+#[test]
+fn test_vote_after_voting_time() {
+    run_scenario("vote-after-voting-time");
+}
+
+#[test]
+fn test_proposal_below_min_weight() {
+    run_scenario("proposal-below-min-weight");
+}
+
+#[test]
+fn test_sign_off_by_non_signatory() {
+    run_scenario("sign-off-by-non-signatory");
+}
+
+#[test]
+fn test_execute_within_hold_up_time() {
+    run_scenario("execute-within-hold-up-time");
+}
+
+#[test]
+fn test_vote_with_expired_voter_weight() {
+    run_scenario("vote-with-expired-voter-weight");
+}
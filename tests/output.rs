@@ -0,0 +1,62 @@
+mod common;
+
+use serde_json::Value;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use spl_governance::state::{
+    enums::VoteTipping,
+    governance::GovernanceV2,
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::output::{ Output, OutputFormat, format_timestamp, format_ui_amount };
+
+#[test]
+fn test_format_values() {
+    assert_eq!(format_ui_amount(1_500_000_000, 9), "1.5");
+    assert_eq!(format_ui_amount(2_000_000, 6), "2");
+    assert_eq!(format_ui_amount(5, 3), "0.005");
+    assert_eq!(format_ui_amount(42, 0), "42");
+
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+    assert_eq!(format_timestamp(1_650_000_000), "2022-04-15 05:20:00 UTC");
+
+    assert_eq!("json".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+    assert!("yaml".parse::<OutputFormat>().is_err());
+}
+
+#[test]
+fn test_render_accounts() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, 3600, 0, 10));
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    let table: String = Output::new(OutputFormat::Table).with_decimals(1).render(&proposal);
+    assert!(table.lines().any(|line| line.starts_with("state") && line.ends_with("Voting") ), "{}", table);
+    assert!(table.lines().any(|line| line.starts_with("voting_at") && line.ends_with("UTC") ), "{}", table);
+
+    let json: Value = serde_json::from_str(&Output::new(OutputFormat::Json).render(&proposal)).unwrap();
+    assert_eq!(json["address"], Value::from(proposal.get_address().to_string()));
+    assert_eq!(json["state"], Value::from("Voting"));
+    assert_eq!(json["closed_at"], Value::Null);
+    assert!(json["voting_at"].is_i64());
+
+    // Option vote weights are amounts as well
+    mock_realm.interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[1], true).unwrap();
+    let proposal: Proposal = mock_realm.interactor.reload_proposal(&proposal).unwrap();
+    let table: String = Output::new(OutputFormat::Table).with_decimals(1).render(&proposal);
+    assert!(table.lines().any(|line| line.starts_with("options") && line.ends_with("Yes: 3") ), "{}", table);
+    let json: Value = serde_json::from_str(&Output::new(OutputFormat::Json).render(&proposal)).unwrap();
+    assert_eq!(json["options"][0]["vote_weight"], Value::from(30u64));
+
+    let json: Value = serde_json::from_str(&Output::new(OutputFormat::Json).render(&mock_realm.voters[0])).unwrap();
+    assert_eq!(json["governing_token_owner"], Value::from(mock_realm.voters[0].get_owner_pubkey().to_string()));
+
+    let governances: Vec<(Pubkey, GovernanceV2)> = mock_realm.interactor.get_governances(&mock_realm.realm.address).unwrap();
+    let json: Value = serde_json::from_str(&Output::new(OutputFormat::Json).render_list(&governances)).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["min_community_weight_to_create_proposal"], Value::from(10u64));
+}
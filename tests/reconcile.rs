@@ -0,0 +1,127 @@
+mod common;
+
+use std::cell::Cell;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use common::MockRealm;
+use governance_test_scripts::address_book::AddressBook;
+use governance_test_scripts::reconcile::{ Change, DesiredGovernance, DesiredState, RealmConfigSpec, apply, plan };
+use governance_test_scripts::scenario::{ GovernanceSpec, VoteTippingSpec };
+
+fn governance_spec(yes_vote_threshold_percentage: u8) -> GovernanceSpec {
+    GovernanceSpec {
+        name: String::new(),
+        yes_vote_threshold_percentage,
+        vote_tipping: VoteTippingSpec::Strict,
+        max_voting_time: 3600,
+        min_transaction_hold_up_time: 0,
+        min_community_weight_to_create_proposal: 1,
+    }
+}
+
+#[test]
+fn test_plan_and_apply() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let existing_governed_account: Pubkey = Pubkey::new_unique();
+    let new_governed_account: Pubkey = Pubkey::new_unique();
+    let mut address_book: AddressBook = AddressBook::default();
+    address_book.insert("new governed", &new_governed_account);
+    interactor.create_governance(&mock_realm.realm, &mock_realm.voters[0], &existing_governed_account, governance_spec(60).get_config()).unwrap();
+
+    // Same addins: only the min weight to create a governance changes
+    let realm_config = interactor.get_realm_config(&mock_realm.realm.address).unwrap();
+    let desired: DesiredState =
+        DesiredState {
+            realm_name: mock_realm.realm.get_name(),
+            realm: Some(
+                RealmConfigSpec {
+                    voter_weight_addin: realm_config.community_voter_weight_addin.map(|addin| addin.to_string() ),
+                    max_voter_weight_addin: realm_config.max_community_voter_weight_addin.map(|addin| addin.to_string() ),
+                    min_community_weight_to_create_governance: 5,
+                    max_vote_weight_supply_fraction: 10_000_000_000,
+                }
+            ),
+            governances: vec![
+                DesiredGovernance { governed_account: existing_governed_account.to_string(), config: governance_spec(50) },
+                DesiredGovernance { governed_account: "new governed".to_string(), config: governance_spec(60) },
+            ],
+            voters: Vec::new(),
+        };
+
+    let changes: Vec<Change> = plan(interactor, &mock_realm.realm, &desired, &address_book).unwrap();
+    assert_eq!(changes.len(), 3);
+    assert!(matches!(&changes[0], Change::SetRealmConfig { drifts, .. } if drifts.len() == 1));
+    assert!(matches!(&changes[1], Change::SetGovernanceConfig { governed_account, .. } if *governed_account == existing_governed_account));
+    assert!(matches!(&changes[2], Change::CreateGovernance { governed_account, .. } if *governed_account == new_governed_account));
+
+    let proposer = &mock_realm.voters[0];
+    for result in apply(interactor, &mock_realm.realm, || Ok(proposer.with_authority(proposer.authority.clone())), &changes) {
+        result.unwrap();
+    }
+
+    // The governance config proposal is pending until it is voted on and executed, and is not created again
+    let governance_pubkey: Pubkey = interactor.get_governance(&mock_realm.realm, &existing_governed_account).unwrap().unwrap().address;
+    let changes: Vec<Change> = plan(interactor, &mock_realm.realm, &desired, &address_book).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert!(matches!(&changes[0], Change::PendingGovernanceConfig { governed_account, .. } if *governed_account == existing_governed_account));
+
+    for result in apply(interactor, &mock_realm.realm, || Ok(proposer.with_authority(proposer.authority.clone())), &changes) {
+        result.unwrap();
+    }
+    assert_eq!(interactor.get_proposals(&governance_pubkey).unwrap().len(), 1);
+}
+
+#[test]
+fn test_proposer_set_up_only_when_needed() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+
+    let realm_config = interactor.get_realm_config(&mock_realm.realm.address).unwrap();
+    let desired: DesiredState =
+        DesiredState {
+            realm_name: mock_realm.realm.get_name(),
+            realm: Some(
+                RealmConfigSpec {
+                    voter_weight_addin: realm_config.community_voter_weight_addin.map(|addin| addin.to_string() ),
+                    max_voter_weight_addin: realm_config.max_community_voter_weight_addin.map(|addin| addin.to_string() ),
+                    min_community_weight_to_create_governance: 5,
+                    max_vote_weight_supply_fraction: 10_000_000_000,
+                }
+            ),
+            governances: Vec::new(),
+            voters: Vec::new(),
+        };
+
+    let changes: Vec<Change> = plan(interactor, &mock_realm.realm, &desired, &AddressBook::default()).unwrap();
+    assert_eq!(changes.len(), 1);
+
+    let set_up_count: Cell<u32> = Cell::new(0);
+    let proposer = &mock_realm.voters[0];
+    let set_up_proposer = || {
+        set_up_count.set(set_up_count.get() + 1);
+        Ok(proposer.with_authority(proposer.authority.clone()))
+    };
+    for result in apply(interactor, &mock_realm.realm, set_up_proposer, &changes) {
+        result.unwrap();
+    }
+    assert_eq!(set_up_count.get(), 0);
+}
+
+#[test]
+fn test_unknown_name() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40]);
+    let desired: DesiredState =
+        DesiredState {
+            realm_name: mock_realm.realm.get_name(),
+            realm: None,
+            governances: vec![
+                DesiredGovernance { governed_account: "unknown".to_string(), config: governance_spec(60) },
+            ],
+            voters: Vec::new(),
+        };
+
+    let error: String = plan(&mock_realm.interactor, &mock_realm.realm, &desired, &AddressBook::default()).unwrap_err();
+    assert!(error.starts_with("governed_account: unknown"), "{}", error);
+}
@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::PathBuf;
+
+use governance_test_scripts::scenario::{ Scenario, ScenarioReport };
+
+/// Every file of the `scenarios` directory must pass
+#[test]
+fn test_scenario_files() {
+    let directory: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+    let mut files: Vec<PathBuf> = fs::read_dir(&directory).unwrap().map(|entry| entry.unwrap().path() ).collect();
+    files.sort();
+    assert!(!files.is_empty());
+
+    for file in files.iter() {
+        let report: ScenarioReport = Scenario::load(file).unwrap().run();
+        assert!(report.is_passed(), "{}:\n{}", file.display(), report);
+    }
+}
+
+fn run_toml(content: &str) -> ScenarioReport {
+    let scenario: Scenario = toml::from_str(content).unwrap();
+    scenario.run()
+}
+
+#[test]
+fn test_duplicate_governance_name() {
+    let report: ScenarioReport = run_toml(r#"
+        name = "duplicate governance"
+        max_voter_weight = 100
+
+        [[voters]]
+        name = "alice"
+        weight = 40
+
+        [[governances]]
+        name = "main"
+        yes_vote_threshold_percentage = 60
+
+        [[governances]]
+        name = "main"
+        yes_vote_threshold_percentage = 50
+    "#);
+    assert_eq!(report.setup, Err("duplicate governance main".to_string()));
+}
+
+#[test]
+fn test_empty_governance_name() {
+    let report: ScenarioReport = run_toml(r#"
+        name = "unnamed governance"
+        max_voter_weight = 100
+
+        [[voters]]
+        name = "alice"
+        weight = 40
+
+        [[governances]]
+        yes_vote_threshold_percentage = 60
+    "#);
+    assert_eq!(report.setup, Err("governance without a name".to_string()));
+}
+
+#[test]
+fn test_unknown_step_key() {
+    let result: Result<Scenario, toml::de::Error> = toml::from_str(r#"
+        name = "misspelled expectation"
+        max_voter_weight = 100
+
+        [[voters]]
+        name = "alice"
+        weight = 40
+
+        [[governances]]
+        name = "main"
+        yes_vote_threshold_percentage = 60
+
+        [[proposals]]
+        name = "first"
+        governance = "main"
+        owner = "alice"
+
+        [[actions]]
+        action = "sign-off"
+        proposal = "first"
+        expect_sate = "Voting"
+    "#);
+    assert!(result.is_err());
+}
@@ -0,0 +1,36 @@
+use std::io;
+
+use solana_sdk::{
+    transaction::{ TransactionError },
+};
+
+use solana_client::client_error::{ ClientError, ClientErrorKind };
+use solana_client::rpc_request::{ RpcError, RpcResponseErrorData };
+
+use governance_test_scripts::send::is_transport_error;
+
+#[test]
+fn test_transport_errors() {
+    let timeout: ClientError = ClientErrorKind::Io(io::Error::new(io::ErrorKind::TimedOut, "timed out")).into();
+    assert!(is_transport_error(&timeout));
+
+    let request_error: ClientError = ClientErrorKind::RpcError(RpcError::RpcRequestError("Failed to deserialize RPC response".to_string())).into();
+    assert!(is_transport_error(&request_error));
+
+    // Answered by the node: resending the same transaction gets the same answer
+    let response_error: ClientError =
+        ClientErrorKind::RpcError(
+            RpcError::RpcResponseError {
+                code: -32002,
+                message: "Transaction simulation failed".to_string(),
+                data: RpcResponseErrorData::Empty,
+            }
+        ).into();
+    assert!(!is_transport_error(&response_error));
+
+    let for_user: ClientError = ClientErrorKind::RpcError(RpcError::ForUser("unable to confirm transaction".to_string())).into();
+    assert!(!is_transport_error(&for_user));
+
+    let transaction_error: ClientError = ClientErrorKind::TransactionError(TransactionError::BlockhashNotFound).into();
+    assert!(!is_transport_error(&transaction_error));
+}
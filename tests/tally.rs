@@ -0,0 +1,150 @@
+mod common;
+
+use spl_governance::state::{
+    enums::{
+        ProposalState,
+        VoteThresholdPercentage,
+        VoteTipping,
+    },
+    governance::GovernanceConfig,
+    proposal::ProposalV2,
+    vote_record::{ Vote, VoteChoice },
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal, yes_no_vote };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::tally::{ VoteTally, cast_vote_and_compare, finalize_vote_and_compare };
+
+const MAX_VOTER_WEIGHT: u64 = 100;
+const VOTER_WEIGHTS: [u64; 4] = [40, 30, 20, 10];
+const MAX_VOTING_TIME: u32 = 3600;
+
+/// Casts `votes` (voter index, yes/no) one by one, then finalizes if the proposal is still voting;
+/// the model must agree with the chain at every step. Returns the final state
+fn run_votes(yes_vote_threshold_percentage: u8, vote_tipping: VoteTipping, votes: &[(usize, bool)]) -> ProposalState {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let interactor = &mock_realm.interactor;
+    let config: GovernanceConfig = gov_config(yes_vote_threshold_percentage, vote_tipping, MAX_VOTING_TIME, 0, 10);
+    let governance: Governance = mock_realm.create_governance(config.clone());
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    let mut tally: VoteTally = VoteTally::new(&config, interactor.get_max_vote_weight(&mock_realm.realm).unwrap(), &proposal.data).unwrap();
+
+    for (voter_index, vote_yes_no) in votes.iter() {
+        if tally.get_state() != ProposalState::Voting {
+            break;
+        }
+        let mismatches: Vec<String> = cast_vote_and_compare(interactor, &mut tally, &mock_realm.realm, &governance, &proposal, &mock_realm.voters[*voter_index], yes_no_vote(*vote_yes_no)).unwrap();
+        assert!(mismatches.is_empty(), "after vote of voter {}: {:?}", voter_index, mismatches);
+    }
+
+    if tally.get_state() == ProposalState::Voting {
+        interactor.advance_past_voting_end(&governance, &proposal).unwrap();
+        let mismatches: Vec<String> = finalize_vote_and_compare(interactor, &mut tally, &mock_realm.realm, &governance, &proposal).unwrap();
+        assert!(mismatches.is_empty(), "after finalization: {:?}", mismatches);
+    }
+
+    tally.get_state()
+}
+
+#[test]
+fn test_strict_tipping_succeeded() {
+    assert_eq!(run_votes(60, VoteTipping::Strict, &[ (0, true), (1, true) ]), ProposalState::Succeeded);
+}
+
+#[test]
+fn test_strict_tipping_defeated() {
+    assert_eq!(run_votes(60, VoteTipping::Strict, &[ (0, false), (1, false) ]), ProposalState::Defeated);
+}
+
+#[test]
+fn test_strict_no_tipping_below_majority() {
+    // 40 reaches the 30% threshold but not the majority of the max vote weight: decided at finalization
+    assert_eq!(run_votes(30, VoteTipping::Strict, &[ (0, true), (3, false) ]), ProposalState::Succeeded);
+}
+
+#[test]
+fn test_early_tipping() {
+    assert_eq!(run_votes(30, VoteTipping::Early, &[ (0, true), (3, false), (1, false) ]), ProposalState::Succeeded);
+}
+
+#[test]
+fn test_disabled_tipping() {
+    assert_eq!(run_votes(60, VoteTipping::Disabled, &[ (0, true), (1, true), (2, false) ]), ProposalState::Succeeded);
+}
+
+#[test]
+fn test_finalization_defeated_by_deny() {
+    assert_eq!(run_votes(30, VoteTipping::Disabled, &[ (0, true), (1, false), (2, false) ]), ProposalState::Defeated);
+}
+
+#[test]
+fn test_quorum_threshold_rejected() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let mut config: GovernanceConfig = gov_config(60, VoteTipping::Strict, MAX_VOTING_TIME, 0, 10);
+    let governance: Governance = mock_realm.create_governance(config.clone());
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    config.vote_threshold_percentage = VoteThresholdPercentage::Quorum(60);
+    assert!(VoteTally::new(&config, MAX_VOTER_WEIGHT, &proposal.data).is_err());
+}
+
+#[test]
+fn test_rejected_votes() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let config: GovernanceConfig = gov_config(60, VoteTipping::Strict, MAX_VOTING_TIME, 0, 10);
+    let governance: Governance = mock_realm.create_governance(config.clone());
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    let mut proposal_without_deny_option: ProposalV2 = proposal.data.clone();
+    proposal_without_deny_option.deny_vote_weight = None;
+    let mut tally: VoteTally = VoteTally::new(&config, MAX_VOTER_WEIGHT, &proposal_without_deny_option).unwrap();
+    assert!(tally.cast_vote(40, &Vote::Deny).is_err());
+    assert!(tally.cast_vote(40, &Vote::Approve(vec![])).is_err());
+
+    let mut tally: VoteTally = VoteTally::new(&config, MAX_VOTER_WEIGHT, &proposal.data).unwrap();
+    assert_eq!(tally.cast_vote(70, &yes_no_vote(true)), Ok(ProposalState::Succeeded));
+    assert!(tally.cast_vote(10, &yes_no_vote(false)).is_err());
+
+    // Only 0% and 100% choices, at most one 100% choice on a single choice proposal
+    let mut proposal_with_two_options: ProposalV2 = proposal.data.clone();
+    proposal_with_two_options.options.push(proposal_with_two_options.options[0].clone());
+    let mut tally: VoteTally = VoteTally::new(&config, MAX_VOTER_WEIGHT, &proposal_with_two_options).unwrap();
+    let choice = |weight_percentage: u8| VoteChoice { rank: 0, weight_percentage };
+    assert!(tally.cast_vote(40, &Vote::Approve(vec![ choice(50), choice(50) ])).is_err());
+    assert!(tally.cast_vote(40, &Vote::Approve(vec![ choice(100), choice(100) ])).is_err());
+    assert_eq!(tally.cast_vote(40, &Vote::Approve(vec![ choice(100), choice(0) ])), Ok(ProposalState::Voting));
+}
+
+#[test]
+fn test_votes_above_max_voter_weight() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let config: GovernanceConfig = gov_config(60, VoteTipping::Disabled, MAX_VOTING_TIME, 0, 10);
+    let governance: Governance = mock_realm.create_governance(config.clone());
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    // 35 + 30 cast of a max voter weight of 50: the threshold is 60% of 65, which 35 misses
+    let mut tally: VoteTally = VoteTally::new(&config, 50, &proposal.data).unwrap();
+    tally.cast_vote(35, &yes_no_vote(true)).unwrap();
+    tally.cast_vote(30, &yes_no_vote(false)).unwrap();
+    assert_eq!(tally.get_max_vote_weight(), 65);
+    assert_eq!(tally.get_min_vote_threshold_weight(), 39);
+    assert_eq!(tally.finalize(), ProposalState::Defeated);
+}
+
+#[test]
+fn test_large_voter_weight() {
+    let mock_realm: MockRealm = MockRealm::new(MAX_VOTER_WEIGHT, &VOTER_WEIGHTS);
+    let config: GovernanceConfig = gov_config(60, VoteTipping::Disabled, MAX_VOTING_TIME, 0, 10);
+    let governance: Governance = mock_realm.create_governance(config.clone());
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+
+    // Weight times percentage would overflow u64
+    let voter_weight: u64 = u64::MAX / 2;
+    let mut tally: VoteTally = VoteTally::new(&config, u64::MAX, &proposal.data).unwrap();
+    assert_eq!(tally.cast_vote(voter_weight, &yes_no_vote(true)), Ok(ProposalState::Voting));
+
+    let mut proposal_data: ProposalV2 = proposal.data.clone();
+    proposal_data.options[0].vote_weight = voter_weight;
+    assert!(tally.get_mismatches(&proposal_data).is_empty(), "{:?}", tally.get_mismatches(&proposal_data));
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use borsh::BorshSerialize;
+
+use solana_sdk::{
+    account::{ Account },
+    pubkey::{ Pubkey },
+};
+
+use spl_governance::state::{
+    enums::{ GovernanceAccountType, InstructionExecutionFlags, ProposalState, VoteTipping },
+    governance::GovernanceV2,
+    legacy::{ GovernanceV1, ProposalV1, RealmV1 },
+    proposal::ProposalV2,
+    realm::RealmV2,
+    token_owner_record::TokenOwnerRecordV2,
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal, SplGovernanceInteractor };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::versioned::{ AccountVersion, DecodeError, Versioned, get_version };
+
+#[test]
+fn test_decode_v2_accounts() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, 3600, 0, 10));
+    let proposal: Proposal = mock_realm.create_proposal(&governance, &mock_realm.voters[0]).unwrap();
+
+    let realm: Versioned<RealmV2> = interactor.get_versioned_account(&mock_realm.realm.address).unwrap();
+    assert_eq!(realm.version, AccountVersion::V2);
+    assert_eq!(realm.data.name, mock_realm.realm.get_name());
+
+    let governance_data: Versioned<GovernanceV2> = interactor.get_versioned_account(&governance.address).unwrap();
+    assert_eq!(governance_data.version, AccountVersion::V2);
+    assert_eq!(governance_data.data.realm, mock_realm.realm.address);
+
+    let proposal_data: Versioned<ProposalV2> = interactor.get_versioned_account(&proposal.get_address()).unwrap();
+    assert_eq!(proposal_data.data.governance, governance.address);
+
+    let token_owner_records: Vec<(Pubkey, TokenOwnerRecordV2)> = interactor.get_token_owner_records(&mock_realm.realm.address).unwrap();
+    let token_owner_record: Versioned<TokenOwnerRecordV2> = interactor.get_versioned_account(&token_owner_records[0].0).unwrap();
+    assert_eq!(token_owner_record.version, AccountVersion::V2);
+}
+
+#[test]
+fn test_wrong_account_type() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Strict, 3600, 0, 10));
+
+    let error: String = interactor.get_versioned_account::<RealmV2>(&governance.address).unwrap_err().to_string();
+    assert!(error.contains("is not a realm"), "{}", error);
+    assert!(error.contains("AccountGovernanceV2"), "{}", error);
+
+    let address: Pubkey = Pubkey::new_unique();
+    assert_eq!(get_version::<ProposalV2>(&address, &[]), Err(DecodeError::Empty { address }));
+    assert_eq!(get_version::<ProposalV2>(&address, &[ 255 ]), Err(DecodeError::UnknownAccountType { address, account_type: 255 }));
+    assert_eq!(get_version::<ProposalV2>(&address, &[ GovernanceAccountType::ProposalV1 as u8 ]), Ok(AccountVersion::V1));
+    assert_eq!(
+        get_version::<ProposalV2>(&address, &[ GovernanceAccountType::RealmV2 as u8 ]),
+        Err(DecodeError::WrongAccountType { address, expected: "proposal", actual: GovernanceAccountType::RealmV2 })
+    );
+}
+
+/// Program owned account with the borsh serialized `data`
+fn set_program_account<T: BorshSerialize>(interactor: &SplGovernanceInteractor, address: &Pubkey, data: &T) {
+    let account: Account =
+        Account {
+            lamports: 1_000_000_000,
+            data: data.try_to_vec().unwrap(),
+            owner: interactor.get_program_address(),
+            executable: false,
+            rent_epoch: 0,
+        };
+    interactor.set_account(address, &account).unwrap();
+}
+
+#[test]
+fn test_decode_v1_accounts() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30]);
+    let interactor = &mock_realm.interactor;
+    let realm_data: &RealmV2 = &mock_realm.realm.data;
+
+    let realm_v1_pubkey: Pubkey = Pubkey::new_unique();
+    let realm_v1: RealmV1 =
+        RealmV1 {
+            account_type: GovernanceAccountType::RealmV1,
+            community_mint: realm_data.community_mint,
+            config: realm_data.config.clone(),
+            reserved: [0; 6],
+            voting_proposal_count: 0,
+            authority: realm_data.authority,
+            name: "V1 Realm".to_string(),
+        };
+    set_program_account(interactor, &realm_v1_pubkey, &realm_v1);
+
+    let realm: Versioned<RealmV2> = interactor.get_versioned_account(&realm_v1_pubkey).unwrap();
+    assert_eq!(realm.version, AccountVersion::V1);
+    assert_eq!(realm.data.name, realm_v1.name);
+    assert_eq!(realm.data.community_mint, realm_v1.community_mint);
+
+    // At the address of a governance of the mock realm, so it is also found by the interactor getters
+    let governed_account_pubkey: Pubkey = Pubkey::new_unique();
+    let governance_v1_pubkey: Pubkey = interactor.get_governance_address(&mock_realm.realm.get_name(), &governed_account_pubkey);
+    let governance_v1: GovernanceV1 =
+        GovernanceV1 {
+            account_type: GovernanceAccountType::AccountGovernanceV1,
+            realm: mock_realm.realm.address,
+            governed_account: governed_account_pubkey,
+            proposals_count: 1,
+            config: gov_config(60, VoteTipping::Strict, 3600, 0, 10),
+            reserved: [0; 6],
+            voting_proposal_count: 0,
+        };
+    set_program_account(interactor, &governance_v1_pubkey, &governance_v1);
+
+    let governance: Versioned<GovernanceV2> = interactor.get_versioned_account(&governance_v1_pubkey).unwrap();
+    assert_eq!(governance.version, AccountVersion::V1);
+    assert_eq!(governance.data.governed_account, governed_account_pubkey);
+    let governance_data: GovernanceV2 = interactor.get_governance_v2(&mock_realm.realm.get_name(), &governed_account_pubkey).unwrap();
+    assert_eq!(governance_data.config, governance_v1.config);
+    assert_eq!(governance_data.proposals_count, 1);
+
+    let proposal_v1_pubkey: Pubkey = Pubkey::new_unique();
+    let proposal_v1: ProposalV1 =
+        ProposalV1 {
+            account_type: GovernanceAccountType::ProposalV1,
+            governance: governance_v1_pubkey,
+            governing_token_mint: realm_data.community_mint,
+            state: ProposalState::Succeeded,
+            token_owner_record: mock_realm.voters[0].token_owner_record_address,
+            signatories_count: 1,
+            signatories_signed_off_count: 1,
+            yes_votes_count: 70,
+            no_votes_count: 30,
+            instructions_executed_count: 0,
+            instructions_count: 0,
+            instructions_next_index: 0,
+            draft_at: 1,
+            signing_off_at: Some(2),
+            voting_at: Some(3),
+            voting_at_slot: Some(3),
+            voting_completed_at: Some(4),
+            executing_at: None,
+            closed_at: None,
+            execution_flags: InstructionExecutionFlags::None,
+            max_vote_weight: Some(100),
+            vote_threshold_percentage: None,
+            name: "V1 Proposal".to_string(),
+            description_link: String::new(),
+        };
+    set_program_account(interactor, &proposal_v1_pubkey, &proposal_v1);
+
+    let proposal: Versioned<ProposalV2> = interactor.get_versioned_account(&proposal_v1_pubkey).unwrap();
+    assert_eq!(proposal.version, AccountVersion::V1);
+    let proposal_data: ProposalV2 = interactor.get_proposal_data(&proposal_v1_pubkey).unwrap();
+    assert_eq!(proposal_data.name, proposal_v1.name);
+    assert_eq!(proposal_data.state, ProposalState::Succeeded);
+    assert_eq!(proposal_data.options[0].vote_weight, 70);
+    assert_eq!(proposal_data.deny_vote_weight, Some(30));
+}
+
+#[test]
+fn test_missing_accounts_are_errors() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40]);
+    let interactor = &mock_realm.interactor;
+    let realm_name: String = mock_realm.realm.get_name();
+
+    assert!(interactor.get_token_owner_record_v2(&Pubkey::new_unique(), &mock_realm.realm.get_community_mint(), &realm_name).is_err());
+    assert!(interactor.get_governance_v2(&realm_name, &Pubkey::new_unique()).is_err());
+    assert!(interactor.get_proposal_data(&Pubkey::new_unique()).is_err());
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use solana_sdk::pubkey::{ Pubkey };
+
+use spl_governance::state::{
+    enums::VoteTipping,
+    proposal::ProposalV2,
+    vote_record::{ Vote, VoteRecordV2 },
+};
+
+use governance_test_scripts::commands::{ Governance, Proposal };
+use common::{ MockRealm, gov_config };
+use governance_test_scripts::tally::get_vote_record_mismatches;
+
+fn get_vote_records(mock_realm: &MockRealm, proposal: &Proposal) -> Vec<VoteRecordV2> {
+    mock_realm.interactor.get_vote_records(&proposal.get_address()).unwrap().into_iter()
+        .map(|(_, vote_record)| vote_record )
+        .collect()
+}
+
+#[test]
+fn test_vote_records_match_proposal() {
+    let mock_realm: MockRealm = MockRealm::new(100, &[40, 30, 20, 10]);
+    let interactor = &mock_realm.interactor;
+    let governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Disabled, 3600, 0, 10));
+    let proposal: Proposal = mock_realm.create_voting_proposal(&governance);
+    let other_governance: Governance = mock_realm.create_governance(gov_config(60, VoteTipping::Disabled, 3600, 0, 10));
+    let other_proposal: Proposal = mock_realm.create_voting_proposal(&other_governance);
+
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[0], true).unwrap();
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[1], false).unwrap();
+    interactor.cast_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[2], true).unwrap();
+    interactor.cast_vote(&mock_realm.realm, &other_governance, &other_proposal, &mock_realm.voters[3], true).unwrap();
+
+    // Relinquished while voting: the record is closed and the weight withdrawn
+    interactor.relinquish_vote(&mock_realm.realm, &governance, &proposal, &mock_realm.voters[2]).unwrap();
+
+    let vote_records: Vec<VoteRecordV2> = get_vote_records(&mock_realm, &proposal);
+    let mut voters: Vec<Pubkey> = vote_records.iter().map(|vote_record| vote_record.governing_token_owner ).collect();
+    voters.sort();
+    let mut expected_voters: Vec<Pubkey> = vec![ mock_realm.voters[0].get_owner_pubkey(), mock_realm.voters[1].get_owner_pubkey() ];
+    expected_voters.sort();
+    assert_eq!(voters, expected_voters);
+    assert!(vote_records.iter().all(|vote_record| !vote_record.is_relinquished ));
+
+    let deny_record: &VoteRecordV2 = vote_records.iter().find(|vote_record| vote_record.vote == Vote::Deny ).unwrap();
+    assert_eq!(deny_record.voter_weight, 30);
+
+    let proposal_data: ProposalV2 = interactor.get_proposal_data(&proposal.get_address()).unwrap();
+    assert!(get_vote_record_mismatches(&proposal_data, &vote_records).is_empty());
+
+    // A missing record shows up as a mismatch
+    assert_eq!(get_vote_record_mismatches(&proposal_data, &vote_records[..1]).len(), 1);
+
+    assert_eq!(get_vote_records(&mock_realm, &other_proposal).len(), 1);
+}